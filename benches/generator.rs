@@ -0,0 +1,34 @@
+use std::time::{Duration, Instant};
+use infinity_loop::export::{GenerationMode, GeneratorVersion, TileWeights, Topology, World, WorldParams};
+
+const SEEDS: u64 = 20;
+
+fn measure(params: &WorldParams, generator: GeneratorVersion) -> Duration {
+    let now = Instant::now();
+    for seed in 0..SEEDS {
+        World::with_generator(seed, params.clone(), generator);
+    }
+    now.elapsed() / SEEDS as u32
+}
+
+fn main() {
+    let boards = [
+        ("hexagonal", WorldParams::default()),
+        ("square", WorldParams::default().with_topology(Topology::Square)),
+        ("wrapping", WorldParams::default().with_wrap(true)),
+        ("unique", WorldParams::default().with_mode(GenerationMode::UniqueSolution)),
+        // tiles without weight make V2 run into contradictions and backtrack, V1 ignores the weights
+        ("busy", WorldParams::default().with_weights(TileWeights::busy())),
+        ("relaxed", WorldParams::default().with_weights(TileWeights::relaxed()))
+    ];
+    for (name, params) in boards {
+        for radius in [4, 8, 16] {
+            let params = params.clone().with_radius(radius);
+            let results: Vec<String> = [GeneratorVersion::V1, GeneratorVersion::V2]
+                .into_iter()
+                .map(|generator| format!("{:?} {:>8.2}ms", generator, measure(&params, generator).as_secs_f64() * 1000.0))
+                .collect();
+            println!("{:<10} radius {:>2}: {}", name, radius, results.join("  "));
+        }
+    }
+}
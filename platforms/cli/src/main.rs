@@ -0,0 +1,422 @@
+use std::io::{ErrorKind, Read, Write};
+use anyhow::{anyhow, bail, ensure, Context};
+use log::LevelFilter;
+use infinity_loop::export::{Difficulty, GenerationMode, GeneratorVersion, LevelPack, PackLevel, ScrambleParams, World};
+
+const USAGE: &str = "\
+Usage: infinity_loop_cli <command> [options] [files]
+
+Commands:
+    generate            print the level for --seed and the level options
+    solve <file>        print the solution of a board together with its par and difficulty
+    validate <files>    check that boards and level packs are solvable (and unique where required)
+    convert <file>      convert between save files and the plain-text board format
+    pack                print a level pack with --count levels, starting at --seed
+
+Level options:
+    --seed <n>  --topology <hexagonal|square>  --radius <n>  --shape <full|\"ring <n>\"|rectangle|triangle|star>
+    --tile-set <standard|complete>  --mode <standard|unique>  --locked <n>  --colors <n>  --wrap
+
+Other options:
+    --json              write saves instead of the plain-text format
+    --scramble          scramble generated boards
+    --name <name>       the name of the level pack
+    --count <n>         the number of levels in the pack
+    --min-score <x>     only use levels with at least this difficulty score
+    --max-score <x>     only use levels with at most this difficulty score
+    --layouts           store the boards in the pack instead of their seeds
+
+Files can be given as - to read from stdin.";
+
+// seeds that are tried for a single pack level before giving up
+const MAX_SEED_SEARCH: u64 = 10000;
+
+#[derive(Debug, Default)]
+struct Options {
+    level: PackLevel,
+    files: Vec<String>,
+    json: bool,
+    scramble: bool,
+    name: Option<String>,
+    count: usize,
+    min_score: Option<f32>,
+    max_score: Option<f32>,
+    layouts: bool
+}
+
+impl Options {
+
+    fn parse(args: &[String]) -> anyhow::Result<Self> {
+        let mut options = Self {
+            count: 10,
+            ..Default::default()
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args
+                .next()
+                .ok_or_else(|| anyhow!("missing value for {}", arg));
+            match arg.as_str() {
+                "--seed" => options.level.seed = value()?.parse()?,
+                "--topology" => options.level.topology = Some(value()?.parse()?),
+                "--radius" => options.level.radius = Some(value()?.parse()?),
+                "--shape" => options.level.shape = Some(value()?.parse()?),
+                "--tile-set" => options.level.tile_set = Some(value()?.parse()?),
+                "--mode" => options.level.mode = Some(parse_mode(value()?)?),
+                "--locked" => options.level.locked_tiles = Some(value()?.parse()?),
+                "--colors" => options.level.colors = Some(value()?.parse()?),
+                "--wrap" => options.level.wrap = Some(true),
+                "--json" => options.json = true,
+                "--scramble" => options.scramble = true,
+                "--name" => options.name = Some(value()?.clone()),
+                "--count" => options.count = value()?.parse()?,
+                "--min-score" => options.min_score = Some(value()?.parse()?),
+                "--max-score" => options.max_score = Some(value()?.parse()?),
+                "--layouts" => options.layouts = true,
+                "-" => options.files.push(arg.clone()),
+                _ if arg.starts_with('-') => bail!("unknown option {}", arg),
+                _ => options.files.push(arg.clone())
+            }
+        }
+        Ok(options)
+    }
+
+    fn file(&self) -> anyhow::Result<&str> {
+        match self.files.as_slice() {
+            [file] => Ok(file),
+            [] => bail!("missing input file"),
+            _ => bail!("expected a single input file")
+        }
+    }
+
+}
+
+fn parse_mode(s: &str) -> anyhow::Result<GenerationMode> {
+    match s {
+        "standard" => Ok(GenerationMode::Standard),
+        "unique" => Ok(GenerationMode::UniqueSolution),
+        _ => bail!("unknown generation mode \"{}\"", s)
+    }
+}
+
+fn read_input(file: &str) -> anyhow::Result<String> {
+    match file {
+        "-" => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        },
+        _ => std::fs::read_to_string(file).with_context(|| format!("can't read {}", file))
+    }
+}
+
+enum Input {
+    Board(World),
+    Pack(LevelPack)
+}
+
+// accepts boards in the plain-text format, world saves, complete game saves and level packs
+fn parse_input(input: &str) -> anyhow::Result<Input> {
+    if !input.trim_start().starts_with('{') {
+        return Ok(Input::Board(input.parse()?));
+    }
+    let mut value: serde_json::Value = serde_json::from_str(input)?;
+    if value.get("levels").is_some() {
+        return Ok(Input::Pack(LevelPack::from_json(input.as_bytes())?));
+    }
+    if value.get("camera").is_some() {
+        value = value
+            .get_mut("world")
+            .ok_or_else(|| anyhow!("the game save doesn't contain a world"))?
+            .take();
+    }
+    Ok(Input::Board(serde_json::from_value(value)?))
+}
+
+fn read_world(file: &str) -> anyhow::Result<World> {
+    match parse_input(&read_input(file)?).with_context(|| format!("can't parse {}", file))? {
+        Input::Board(world) => Ok(world),
+        Input::Pack(_) => bail!("{} is a level pack and not a single board", file)
+    }
+}
+
+fn print_world(world: &World, json: bool) -> anyhow::Result<()> {
+    match json {
+        true => writeln!(std::io::stdout(), "{}", serde_json::to_string_pretty(world)?)?,
+        false => write!(std::io::stdout(), "{}", world)?
+    }
+    Ok(())
+}
+
+fn create_world(options: &Options) -> anyhow::Result<World> {
+    let mut world = options.level.create()?;
+    if options.scramble {
+        world.scramble(ScrambleParams::default().with_force_rotation(true))?;
+    }
+    Ok(world)
+}
+
+fn generate(options: &Options) -> anyhow::Result<()> {
+    print_world(&create_world(options)?, options.json)
+}
+
+fn solve(options: &Options) -> anyhow::Result<()> {
+    let world = read_world(options.file()?)?;
+    let solutions = world.solutions(2);
+    let solution = solutions
+        .first()
+        .ok_or_else(|| anyhow!("the board has no solution"))?;
+    eprintln!("solutions: {}", match solutions.len() {
+        1 => "unique",
+        _ => "multiple"
+    });
+    if let Some(par) = world.par() {
+        eprintln!("par: {}", par);
+    }
+    if let Some(difficulty) = world.difficulty() {
+        eprintln!("difficulty: {} ({:.2})", difficulty.label(), difficulty.score());
+    }
+    print_world(solution, options.json)
+}
+
+fn check(world: &World) -> anyhow::Result<String> {
+    let solutions = world.solutions(2).len();
+    ensure!(solutions > 0, "no solution");
+    ensure!(solutions == 1 || world.params().mode != GenerationMode::UniqueSolution,
+        "multiple solutions although a unique one is required");
+    let solutions = match solutions {
+        1 => "unique solution",
+        _ => "multiple solutions"
+    };
+    let difficulty = world
+        .difficulty()
+        .map_or_else(|| String::from("-"), |d| d.label().to_string());
+    Ok(match world.is_completed() {
+        true => format!("{}, {}", solutions, difficulty),
+        false => format!("{}, par {}, {}", solutions, world.par().unwrap_or(0), difficulty)
+    })
+}
+
+// every board of an input, levels of packs that can't be created are reported like invalid boards
+fn boards(name: &str, input: &str) -> Vec<(String, anyhow::Result<World>)> {
+    match parse_input(input) {
+        Ok(Input::Board(world)) => vec![(name.to_string(), Ok(world))],
+        Ok(Input::Pack(pack)) => pack.levels
+            .iter()
+            .enumerate()
+            .map(|(i, level)| (format!("{} level {}", name, i + 1), level.create()))
+            .collect(),
+        Err(err) => vec![(name.to_string(), Err(err))]
+    }
+}
+
+// writes one line per board and fails if any of them is invalid
+fn report(boards: Vec<(String, anyhow::Result<World>)>, out: &mut impl Write) -> anyhow::Result<()> {
+    let boards_checked = boards.len();
+    let mut failed = 0;
+    for (name, world) in boards {
+        match world.and_then(|world| check(&world)) {
+            Ok(summary) => writeln!(out, "{}: ok ({})", name, summary)?,
+            Err(err) => {
+                writeln!(out, "{}: {:#}", name, err)?;
+                failed += 1;
+            }
+        }
+    }
+    ensure!(failed == 0, "{} of {} boards failed validation", failed, boards_checked);
+    Ok(())
+}
+
+fn validate(options: &Options) -> anyhow::Result<()> {
+    ensure!(!options.files.is_empty(), "missing input file");
+    let boards = options.files
+        .iter()
+        .flat_map(|file| match read_input(file) {
+            Ok(input) => boards(file, &input),
+            Err(err) => vec![(file.clone(), Err(err))]
+        })
+        .collect();
+    report(boards, &mut std::io::stdout())
+}
+
+fn convert(options: &Options) -> anyhow::Result<()> {
+    let world = read_world(options.file()?)?;
+    print_world(&world, options.json)
+}
+
+fn create_pack(options: &Options) -> anyhow::Result<LevelPack> {
+    let name = options.name
+        .clone()
+        .ok_or_else(|| anyhow!("missing --name for the level pack"))?;
+    let params = options.level.params();
+    let band = options.min_score.unwrap_or(f32::NEG_INFINITY)..=options.max_score.unwrap_or(f32::INFINITY);
+    let mut levels = Vec::new();
+    let mut next = options.level.seed;
+    for i in 0..options.count {
+        let seed = Difficulty::find_seed(next..next + MAX_SEED_SEARCH, params.clone(), band.clone())
+            .ok_or_else(|| anyhow!("no seed from {} on matches the difficulty for level {}", next, i + 1))?;
+        log::info!("Level {}: seed {}", i + 1, seed);
+        let level = PackLevel {
+            seed,
+            version: Some(GeneratorVersion::LATEST),
+            ..options.level.clone()
+        };
+        // the options stay next to the layout as they also tell how the level has to be solvable
+        levels.push(match options.layouts {
+            true => PackLevel {
+                layout: Some(level.create()?.to_string()),
+                ..level
+            },
+            false => level
+        });
+        next = seed + 1;
+    }
+    Ok(LevelPack {
+        name,
+        levels
+    })
+}
+
+fn pack(options: &Options) -> anyhow::Result<()> {
+    writeln!(std::io::stdout(), "{}", serde_json::to_string_pretty(&create_pack(options)?)?)?;
+    Ok(())
+}
+
+fn run(args: &[String]) -> anyhow::Result<()> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+    };
+    let options = Options::parse(args)?;
+    match command {
+        "generate" => generate(&options),
+        "solve" => solve(&options),
+        "validate" => validate(&options),
+        "convert" => convert(&options),
+        "pack" => pack(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        _ => bail!("unknown command \"{}\"\n\n{}", command, USAGE)
+    }
+}
+
+fn main() {
+    env_logger::builder()
+        .filter_level(LevelFilter::Warn)
+        .parse_default_env()
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        // the output was piped into a program that stopped reading
+        if err.downcast_ref::<std::io::Error>().is_some_and(|err| err.kind() == ErrorKind::BrokenPipe) {
+            return;
+        }
+        eprintln!("error: {:#}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &str) -> Options {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        Options::parse(&args).unwrap()
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = options("--seed 7 --topology square --radius 3 --mode unique --locked 2 --wrap --json board.txt");
+        assert_eq!(options.level.seed, 7);
+        assert_eq!(options.level.params().radius, 3);
+        assert_eq!(options.level.mode, Some(GenerationMode::UniqueSolution));
+        assert_eq!(options.level.locked_tiles, Some(2));
+        assert_eq!(options.level.wrap, Some(true));
+        assert!(options.json);
+        assert_eq!(options.file().unwrap(), "board.txt");
+        assert!(Options::parse(&[String::from("--bogus")]).is_err());
+        assert!(Options::parse(&[String::from("--seed")]).is_err());
+        assert!(Options::parse(&[String::from("--mode"), String::from("hard")]).is_err());
+    }
+
+    #[test]
+    fn generate() {
+        let world = create_world(&options("--seed 3 --radius 3")).unwrap();
+        assert_eq!(world.to_string(), World::with_params(3, options("--radius 3").level.params()).to_string());
+        assert!(world.is_completed());
+        assert!(!create_world(&options("--seed 3 --radius 3 --scramble")).unwrap().is_completed());
+    }
+
+    #[test]
+    fn inputs() {
+        let world = create_world(&options("--seed 4 --radius 2 --locked 2")).unwrap();
+        let saves = [
+            world.to_string(),
+            serde_json::to_string(&world).unwrap(),
+            format!("{{\"camera\": {{}}, \"world\": {}}}", serde_json::to_string(&world).unwrap())
+        ];
+        for save in saves {
+            match parse_input(&save).unwrap() {
+                Input::Board(parsed) => assert_eq!(parsed.to_string(), world.to_string()),
+                Input::Pack(_) => panic!("parsed a board as a level pack")
+            }
+        }
+        let pack = include_str!("../../../src/packs/tutorial.json");
+        assert!(matches!(parse_input(pack).unwrap(), Input::Pack(pack) if pack.name == "Tutorial"));
+        assert!(parse_input("{\"camera\": {}}").is_err());
+    }
+
+    #[test]
+    fn validation() {
+        let world = create_world(&options("--seed 9 --radius 3 --mode unique")).unwrap();
+        assert!(check(&world).unwrap().starts_with("unique solution"));
+        let unsolvable: World = "topology: square\nradius: 0\n\n01\n".parse().unwrap();
+        assert!(check(&unsolvable).is_err());
+    }
+
+    #[test]
+    fn layouts() {
+        let pack = create_pack(&options("--name Test --count 2 --seed 20 --radius 3 --mode unique --layouts")).unwrap();
+        assert_eq!(pack.len(), 2);
+        for level in &pack.levels {
+            assert!(level.layout.is_some());
+            assert_eq!(level.mode, Some(GenerationMode::UniqueSolution));
+            assert_eq!(level.radius, Some(3));
+            assert_eq!(level.version, Some(GeneratorVersion::LATEST));
+        }
+        let pack = LevelPack::from_json(serde_json::to_string(&pack).unwrap().as_bytes()).unwrap();
+        for i in 0..pack.len() {
+            let world = pack.level(i).unwrap();
+            assert_eq!(world.params().mode, GenerationMode::UniqueSolution);
+            assert!(check(&world).is_ok());
+        }
+    }
+
+    #[test]
+    fn invalid_pack_level() {
+        let pack = r#"{"name": "Broken", "levels": [{"seed": 1, "radius": 2}, {"seed": 1, "radius": 1000}]}"#;
+        let boards = boards("broken.json", pack);
+        assert_eq!(boards.len(), 2);
+        assert!(boards[0].1.is_ok());
+        assert_eq!(boards[1].0, "broken.json level 2");
+        assert!(boards[1].1.is_err());
+        let mut out = Vec::new();
+        let result = report(boards, &mut out);
+        assert_eq!(result.unwrap_err().to_string(), "1 of 2 boards failed validation");
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("broken.json level 1: ok"));
+        assert!(lines[1].starts_with("broken.json level 2: "));
+    }
+
+}
@@ -8,13 +8,13 @@ use glutin::display::{Display, GetGlDisplay, GlDisplay};
 use glutin::surface::{GlSurface, Surface, WindowSurface};
 use glutin_winit::{ApiPrefence, DisplayBuilder, finalize_window};
 use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
-use winit::event::{ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, Touch, TouchPhase, VirtualKeyCode, WindowEvent};
+use winit::event::{ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, Touch, TouchPhase, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget};
 use winit::window::{Fullscreen, Window, WindowBuilder};
 use log::{LevelFilter};
 use raw_window_handle::HasRawWindowHandle;
 use infinity_loop::{InfinityLoop};
-use infinity_loop::export::{AppContext, Application, Context, GlowContext, Result};
+use infinity_loop::export::{AppContext, Application, Context, GlowContext, LevelPack, Result};
 
 pub struct GlutinWindowContext {
     window: Window,
@@ -184,6 +184,17 @@ fn main() {
 
     let mut app = Application::<InfinityLoop, GlutinContext>::new(std::fs::read_to_string(save_file).ok()).unwrap();
 
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--pack", Some(path)) => match LevelPack::load(&path) {
+                Ok(pack) => app.update_bundle(|bundle| bundle.start_pack(pack)),
+                Err(err) => log::error!("{:#}", err)
+            },
+            _ => log::warn!("Ignoring unknown argument {}", arg)
+        }
+    }
+
     let event_loop = EventLoop::new();
 
     app.resume(|| GlutinContext::new(&event_loop));
@@ -192,6 +203,7 @@ fn main() {
     let mut ctx = None;
     let mut pos = PhysicalPosition::new(0.0, 0.0);
     let mut down = false;
+    let mut modifiers = ModifiersState::empty();
     let mut touch_stack = VecDeque::new();
     event_loop.run(move |event, event_loop, control_flow| {
         *control_flow = match app.should_redraw() {
@@ -236,6 +248,7 @@ fn main() {
                     TouchPhase::Ended => app.on_release(location.x as f32, location.y as f32, id),
                     TouchPhase::Cancelled => app.on_release(location.x as f32, location.y as f32, id)
                 },
+                WindowEvent::ModifiersChanged(state) => modifiers = state,
                 WindowEvent::KeyboardInput {  input: KeyboardInput {  state: ElementState::Pressed, virtual_keycode, .. }, .. } => match virtual_keycode{
                     Some(VirtualKeyCode::F11) => app.with_ctx(|ctx| {
                         let window = ctx.0.window();
@@ -250,6 +263,13 @@ fn main() {
                         app = Application::<InfinityLoop, GlutinContext>::new(None).unwrap();
                         app.resume(||Ok(ctx.take().unwrap()));
                     },
+                    Some(VirtualKeyCode::H) => app.on_hint(),
+                    Some(VirtualKeyCode::D) => app.on_daily(),
+                    Some(VirtualKeyCode::S) => app.on_stats(),
+                    Some(VirtualKeyCode::Escape) => app.on_back(),
+                    Some(VirtualKeyCode::Z) if modifiers.ctrl() && modifiers.shift() => app.on_redo(),
+                    Some(VirtualKeyCode::Z) if modifiers.ctrl() => app.on_undo(),
+                    Some(VirtualKeyCode::Y) if modifiers.ctrl() => app.on_redo(),
                     Some(VirtualKeyCode::Return) => {
                         app.on_press(pos.x as f32, pos.y as f32, 1 + touch_stack.len() as u64);
                         touch_stack.push_back(pos);
@@ -30,6 +30,16 @@ window.addEventListener('beforeunload', e => {
     callback('Unloading');
 })
 
+window.addEventListener('keydown', e => {
+    if (e.ctrlKey && e.code === 'KeyZ') {
+        callback(e.shiftKey ? 'Redo' : 'Undo');
+        e.preventDefault();
+    } else if (e.ctrlKey && e.code === 'KeyY') {
+        callback('Redo');
+        e.preventDefault();
+    }
+})
+
 canvas.addEventListener('mousemove', e => {
     let dpi = window.devicePixelRatio;
     callback({'MouseMove': {'x': e.x * dpi, 'y': e.y * dpi}});
@@ -36,7 +36,9 @@ pub enum JsEvent {
     },
     Redraw,
     Unloading,
-    Timeout
+    Timeout,
+    Undo,
+    Redo
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
@@ -113,6 +113,8 @@ fn main() -> std::result::Result<(), JsValue> {
                 app.process_timeouts();
                 current_timeout = None;
             }
+            JsEvent::Undo => app.on_undo(),
+            JsEvent::Redo => app.on_redo()
         }
         if app.should_save() {
             app.save(|s| Ok(storage.set_item(save_key, &s).unwrap())).unwrap();
@@ -23,7 +23,13 @@ pub enum Event {
     Drag(Vec2),
     Zoom(Vec2, f32, bool),
     TouchStart,
-    TouchEnd
+    TouchEnd,
+    Hint,
+    Undo,
+    Redo,
+    Daily,
+    Stats,
+    Back
 }
 
 enum ApplicationState<G: Game, A: AppContext> {
@@ -116,6 +122,16 @@ impl<G: Game, A: AppContext> Application<G, A> {
         })
     }
 
+    pub fn update_bundle(&mut self, func: impl FnOnce(G::Bundle) -> G::Bundle) {
+        self.state = match take(&mut self.state) {
+            ApplicationState::Suspended(bundle) => ApplicationState::Suspended(func(bundle)),
+            state => {
+                log::warn!("The bundle can only be updated while the app is suspended");
+                state
+            }
+        }
+    }
+
     pub fn resume(&mut self, ctx_func: impl FnOnce() -> Result<A>) {
         self.state = match take(&mut self.state) {
             ApplicationState::Suspended(bundle) => match ctx_func() {
@@ -237,6 +253,30 @@ impl<G: Game, A: AppContext> Application<G, A> {
         self.call_event(Event::Zoom(self.normalize(x, y), amt, true))
     }
 
+    pub fn on_hint(&mut self) {
+        self.call_event(Event::Hint)
+    }
+
+    pub fn on_undo(&mut self) {
+        self.call_event(Event::Undo)
+    }
+
+    pub fn on_redo(&mut self) {
+        self.call_event(Event::Redo)
+    }
+
+    pub fn on_daily(&mut self) {
+        self.call_event(Event::Daily)
+    }
+
+    pub fn on_stats(&mut self) {
+        self.call_event(Event::Stats)
+    }
+
+    pub fn on_back(&mut self) {
+        self.call_event(Event::Back)
+    }
+
     fn normalize(&self, x: f32, y: f32) -> Vec2 {
         let (width, height) = self.screen_size;
         Vec2::new(x / width as f32, 1.0 - y / height as f32)
@@ -0,0 +1,207 @@
+use std::time::Duration;
+use fastrand::Rng;
+use instant::SystemTime;
+use serde::{Serialize, Deserialize};
+use crate::stats::format_time;
+use crate::types::Topology;
+use crate::world::{BoardShape, GenerationMode, TileSet, World, WorldParams};
+use crate::game::LevelProgress;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DailyChallenge {
+    // days since the unix epoch (utc) of the current puzzle
+    day: u64,
+    world: Option<World>,
+    #[serde(default)]
+    progress: LevelProgress,
+    started: Option<u64>,
+    time: Option<u64>,
+    last_completed: Option<u64>,
+    streak: u32,
+    best_streak: u32
+}
+
+impl DailyChallenge {
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+    }
+
+    pub fn today() -> u64 {
+        Self::now() / SECONDS_PER_DAY
+    }
+
+    pub fn params(day: u64) -> WorldParams {
+        let rng = Rng::with_seed(day);
+        // the puzzles get harder over the course of the week, starting on monday
+        let weekday = (day + 3) % 7;
+        let radius = 3 + weekday as i32 / 2;
+        let topology = match rng.u8(..4) {
+            0 => Topology::Square,
+            _ => Topology::Hexagonal
+        };
+        let shape = match rng.u8(..6) {
+            0 => BoardShape::Ring(1),
+            1 => BoardShape::Star,
+            _ => BoardShape::Full
+        };
+        let tile_set = match weekday >= 5 {
+            true => TileSet::Complete,
+            false => TileSet::Standard
+        };
+        WorldParams::default()
+            .with_topology(topology)
+            .with_tile_set(tile_set)
+            .with_shape(shape)
+            .with_radius(radius)
+            .with_mode(GenerationMode::UniqueSolution)
+            .with_colors(1 + rng.u8(..2))
+    }
+
+    // starts over if the saved puzzle is from a previous day
+    pub fn refresh(&mut self) {
+        let today = Self::today();
+        if self.day != today {
+            self.day = today;
+            self.world = None;
+            self.progress = LevelProgress::default();
+            self.started = None;
+            self.time = None;
+        }
+    }
+
+    pub fn world(&mut self) -> World {
+        self.refresh();
+        self.world
+            .clone()
+            .unwrap_or_else(|| World::with_params(self.day, Self::params(self.day)))
+    }
+
+    pub fn save_progress(&mut self, world: World, progress: LevelProgress) {
+        self.world = Some(world);
+        self.progress = progress;
+    }
+
+    pub fn progress(&self) -> LevelProgress {
+        self.progress.clone()
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.time.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.started = Some(Self::now());
+    }
+
+    // takes the playing time of the level, which unlike the wall clock stops while the game is suspended
+    pub fn complete(&mut self, time: Duration) {
+        if self.is_completed() {
+            return;
+        }
+        self.time = Some(time.as_secs());
+        self.streak = match self.last_completed {
+            Some(day) if day + 1 == self.day => self.streak + 1,
+            _ => 1
+        };
+        self.best_streak = self.best_streak.max(self.streak);
+        self.last_completed = Some(self.day);
+        log::info!("Completed the daily challenge in {}s (streak: {})", self.time.unwrap_or(0), self.streak);
+    }
+
+    pub fn streak(&self) -> u32 {
+        match self.last_completed {
+            Some(day) if day + 1 >= Self::today() => self.streak,
+            _ => 0
+        }
+    }
+
+    pub fn date(&self) -> String {
+        format_day(self.day)
+    }
+
+    pub fn summary(&self) -> String {
+        let streak = format!("Streak {} / Best {}", self.streak(), self.best_streak);
+        match self.time {
+            Some(time) => format!("Solved in {}\n{}", format_time(time), streak),
+            None => streak
+        }
+    }
+
+}
+
+pub fn format_day(days: u64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = match mp < 10 {
+        true => mp + 3,
+        false => mp - 9
+    };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(format_day(11016), "2000-02-29");
+        assert_eq!(format_day(19782), "2024-02-29");
+        assert_eq!(format_day(19783), "2024-03-01");
+        assert_eq!(format_day(47541), "2100-03-01");
+        assert_eq!(format_day(19722), "2023-12-31");
+        assert_eq!(format_day(19723), "2024-01-01");
+    }
+
+    #[test]
+    fn stable_puzzles() {
+        // 2024-01-01 was a monday and gets the easiest puzzle of its week
+        let monday = 19723;
+        assert_eq!(DailyChallenge::params(monday), WorldParams::default()
+            .with_radius(3)
+            .with_mode(GenerationMode::UniqueSolution)
+            .with_colors(2));
+        assert_eq!(DailyChallenge::params(monday + 3).radius, 4);
+        assert_eq!(DailyChallenge::params(monday + 6).tile_set, TileSet::Complete);
+        let world = World::with_params(monday, DailyChallenge::params(monday));
+        assert_eq!(world.to_string(), World::with_params(monday, DailyChallenge::params(monday)).to_string());
+        assert_ne!(world.to_string(), World::with_params(monday + 7, DailyChallenge::params(monday + 7)).to_string());
+    }
+
+    #[test]
+    fn completion_time() {
+        let mut daily = DailyChallenge::default();
+        daily.refresh();
+        daily.start();
+        daily.complete(Duration::from_secs(75));
+        assert_eq!(daily.time, Some(75));
+        assert_eq!(daily.streak(), 1);
+        // only the first completion counts
+        daily.complete(Duration::from_secs(5));
+        assert_eq!(daily.time, Some(75));
+        assert_eq!(daily.streak(), 1);
+    }
+
+}
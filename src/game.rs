@@ -0,0 +1,697 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::Duration;
+use artery_font::ArteryFont;
+use glam::Vec2;
+use instant::Instant;
+use serde::{Serialize, Deserialize};
+
+use crate::app::{AppContext, Event, EventResponse, Game, SaveRequest};
+use crate::camera::{AnimatedCamera, Camera};
+use crate::daily::DailyChallenge;
+use crate::stats::{LevelKey, Solve, Stats};
+use crate::types::{HexPos, Rgba, Topology};
+use crate::world::{BoardShape, Direction, GenerationMode, TileSet, History, LevelPack, ScrambleParams, World, WorldParams};
+use crate::renderer::{Anchor, GameRenderer, GameState, RenderableWorld, TextAlignment, TextBuffer, TextRenderer, TileRenderResources};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+enum Progression {
+    #[default]
+    Endless,
+    Pack {
+        pack: LevelPack,
+        level: usize,
+        // the endless level to continue with once the pack is completed
+        resume_level: u64
+    }
+}
+
+// the state of a single level, the daily challenge keeps its own copy
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelProgress {
+    history: History,
+    moves: u32,
+    par: Option<u32>,
+    level_hints: u32,
+    level_time: Duration
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InfinityLoopBundle {
+    world: World,
+    camera: Camera,
+    state: GameState,
+    #[serde(default)]
+    hints: u32,
+    #[serde(flatten)]
+    progress: LevelProgress,
+    // replaced by `stats`, only read from older saves
+    #[serde(default, skip_serializing)]
+    best: BTreeMap<u64, u32>,
+    #[serde(default)]
+    stats: Stats,
+    #[serde(default)]
+    progression: Progression,
+    #[serde(default)]
+    daily: DailyChallenge,
+    // the daily challenge is shown instead of `world`
+    #[serde(default)]
+    daily_active: bool
+}
+
+impl InfinityLoopBundle {
+
+    pub fn start_pack(self, pack: LevelPack) -> Self {
+        let resume_level = match &self.progression {
+            Progression::Endless => self.world.seed(),
+            Progression::Pack { resume_level, .. } => *resume_level
+        };
+        match pack.level(0) {
+            Some(world) => Self {
+                world,
+                state: GameState::Tutorial,
+                daily_active: false,
+                hints: 0,
+                progress: LevelProgress::default(),
+                progression: Progression::Pack {
+                    pack,
+                    level: 0,
+                    resume_level
+                },
+                ..self
+            },
+            None => {
+                log::warn!("Level pack \"{}\" has no levels", pack.name);
+                self
+            }
+        }
+    }
+
+}
+
+impl Default for InfinityLoopBundle {
+    fn default() -> Self {
+        let camera = Camera {
+            scale: 6.0,
+            ..Default::default()
+        };
+
+        let tutorial = LevelPack::bundled().next().unwrap();
+        let world = tutorial.level(0).unwrap();
+        //world.scramble();
+
+        //let state = match world.is_completed() {
+        //    true => GameState::Ending(Vec2::ZERO, f32::INFINITY),
+        //    false => GameState::Tutorial
+        //};
+        let state = GameState::Tutorial;
+
+        Self {
+            world,
+            camera,
+            state,
+            hints: 0,
+            progress: LevelProgress::default(),
+            best: BTreeMap::new(),
+            stats: Stats::default(),
+            progression: Progression::Pack {
+                pack: tutorial,
+                level: 0,
+                resume_level: 1
+            },
+            daily: DailyChallenge::default(),
+            daily_active: false
+        }
+    }
+}
+
+pub struct InfinityLoop {
+    renderer: GameRenderer,
+    camera: AnimatedCamera,
+    world: RenderableWorld,
+    old_world: RenderableWorld,
+    text_renderer: TextRenderer,
+    text_buffer: TextBuffer,
+    buttons: Vec<(Event, TextBuffer)>,
+    score_text: TextBuffer,
+    state: GameState,
+    hints: u32,
+    progress: LevelProgress,
+    stats: Stats,
+    last_activity: Instant,
+    progression: Progression,
+    daily: DailyChallenge,
+    // the main world and its progress are parked here while the daily challenge is played
+    main_world: Option<(World, LevelProgress)>
+}
+
+impl InfinityLoop {
+
+    const MAX_RADIUS: i32 = 6;
+    const LEVELS_PER_RADIUS: u64 = 10;
+    const SQUARE_LEVEL_INTERVAL: u64 = 5;
+    const MIN_SHAPED_RADIUS: i32 = 3;
+    const MIN_LOCKED_RADIUS: i32 = 4;
+    const LOCKED_TILES_PER_RADIUS: usize = 2;
+    const MIN_COMPLETE_TILE_SET_RADIUS: i32 = 5;
+    const COLORED_LEVEL_INTERVAL: u64 = 7;
+    const LEVEL_COLORS: u8 = 3;
+    const WRAPPED_LEVEL_INTERVAL: u64 = 9;
+    // share of the rotatable tiles that has to be turned away from the solution
+    const MIN_MISPLACED_PERCENT: u32 = 50;
+
+    fn level_params(level: u64) -> WorldParams {
+        let base = WorldParams::default();
+        let radius = base.radius as u64 + level.saturating_sub(1) / Self::LEVELS_PER_RADIUS;
+        let radius = radius.min(Self::MAX_RADIUS as u64) as i32;
+        let topology = match level % Self::SQUARE_LEVEL_INTERVAL {
+            0 => Topology::Square,
+            _ => Topology::Hexagonal
+        };
+        let wrap = matches!(level % Self::WRAPPED_LEVEL_INTERVAL, 0);
+        let shape = match level % 6 {
+            _ if radius < Self::MIN_SHAPED_RADIUS || wrap => BoardShape::Full,
+            1 => BoardShape::Ring(1),
+            2 => BoardShape::Rectangle,
+            3 => BoardShape::Triangle,
+            4 => BoardShape::Star,
+            5 => BoardShape::Ring(radius / 2),
+            _ => BoardShape::Full
+        };
+        let locked_tiles = (radius - Self::MIN_LOCKED_RADIUS + 1).max(0) as usize * Self::LOCKED_TILES_PER_RADIUS;
+        let tile_set = match radius >= Self::MIN_COMPLETE_TILE_SET_RADIUS {
+            true => TileSet::Complete,
+            false => TileSet::Standard
+        };
+        let colors = match level % Self::COLORED_LEVEL_INTERVAL {
+            0 => Self::LEVEL_COLORS,
+            _ => 1
+        };
+        base.with_topology(topology)
+            .with_tile_set(tile_set)
+            .with_shape(shape)
+            .with_radius(radius)
+            .with_mode(GenerationMode::UniqueSolution)
+            .with_locked_tiles(locked_tiles)
+            .with_colors(colors)
+            .with_wrap(wrap)
+    }
+
+    fn create_level(level: u64) -> World {
+        World::with_params(level, Self::level_params(level))
+    }
+
+    fn next_level(&mut self) -> World {
+        if let Progression::Pack { pack, level, resume_level } = &mut self.progression {
+            *level += 1;
+            match pack.level(*level) {
+                Some(world) => return world,
+                None => {
+                    log::info!("Completed level pack \"{}\"", pack.name);
+                    let resume_level = *resume_level;
+                    self.progression = Progression::Endless;
+                    return Self::create_level(resume_level);
+                }
+            }
+        }
+        Self::create_level(self.world.seed() + 1)
+    }
+
+    fn previous_level(progression: &Progression, world: &World) -> World {
+        match progression {
+            Progression::Endless => Self::create_level(world.seed().saturating_sub(1)),
+            Progression::Pack { pack, level, .. } => pack
+                .level(level.saturating_sub(1))
+                .unwrap_or_else(|| world.clone())
+        }
+    }
+
+    fn show_hint(&mut self) -> bool {
+        match self.world.hint() {
+            Some((pos, direction, steps)) => {
+                for _ in 0..steps {
+                    self.progress.history.push(pos, direction);
+                }
+                self.hints += 1;
+                self.progress.level_hints += 1;
+                log::debug!("Revealed tile {:?} (hints used: {})", pos, self.hints);
+                self.check_completion(self.world.topology().to_point(pos));
+                true
+            },
+            None => false
+        }
+    }
+
+    // undo, redo and hints don't count as moves
+    fn replay(&mut self, (pos, direction): (HexPos, Direction)) -> bool {
+        let rotated = self.world.try_rotate(pos, direction);
+        self.check_completion(self.world.topology().to_point(pos));
+        rotated
+    }
+
+    fn start_level(&mut self) {
+        self.progress = LevelProgress {
+            par: self.world.par(),
+            ..Default::default()
+        };
+        self.score_text.set_text(&self.score_summary(), TextAlignment::Right);
+    }
+
+    // includes the time since the last event, which can be long as events only arrive on input
+    fn current_progress(&self) -> LevelProgress {
+        let mut progress = self.progress.clone();
+        if matches!(self.state, GameState::InProgress) {
+            progress.level_time += self.last_activity.elapsed();
+        }
+        progress
+    }
+
+    fn record_move(&mut self) {
+        self.progress.moves += 1;
+        self.score_text.set_text(&self.score_summary(), TextAlignment::Right);
+    }
+
+    fn check_completion(&mut self, center: Vec2) {
+        if self.world.is_completed() {
+            if let Some(key) = self.level_key() {
+                self.stats.record(key, Solve::new(self.progress.level_time.as_secs(), self.progress.moves, self.progress.level_hints));
+            }
+            if self.main_world.is_some() {
+                self.daily.complete(self.progress.level_time);
+            }
+            self.state.set(GameState::WaitingForEnd(center));
+        }
+    }
+
+    // the daily challenge keeps track of its own time and streak
+    fn level_key(&self) -> Option<LevelKey> {
+        match (&self.progression, &self.main_world) {
+            (_, Some(_)) => None,
+            (Progression::Endless, None) => Some(LevelKey::Endless(self.world.seed())),
+            (Progression::Pack { pack, level, .. }, None) => Some(LevelKey::Pack(pack.name.clone(), *level))
+        }
+    }
+
+    fn show_stats(&mut self) {
+        self.state.set(GameState::Stats);
+        self.text_buffer.set_text(&self.stats.summary(self.level_key().as_ref()), TextAlignment::Center);
+        self.text_buffer.offset = Vec2::new(0.0, -10.0);
+    }
+
+    fn enter_daily(&mut self) {
+        let daily = self.daily.world();
+        let progress = std::mem::replace(&mut self.progress, self.daily.progress());
+        self.main_world = Some(((&self.world).into(), progress));
+        std::mem::swap(&mut self.world, &mut self.old_world);
+        self.world.reinitialize(daily);
+        self.score_text.set_text(&self.score_summary(), TextAlignment::Right);
+        self.state.set(GameState::Daily);
+        self.text_buffer.set_text(&Self::daily_intro(&self.daily), TextAlignment::Center);
+        self.text_buffer.offset = Vec2::new(0.0, -10.0);
+    }
+
+    // an unfinished daily challenge can be continued later
+    fn leave_daily(&mut self, center: Vec2) {
+        if let Some((main_world, progress)) = self.main_world.take() {
+            let daily_progress = std::mem::replace(&mut self.progress, progress);
+            self.daily.save_progress((&self.world).into(), daily_progress);
+            std::mem::swap(&mut self.world, &mut self.old_world);
+            self.world.reinitialize(main_world);
+            self.score_text.set_text(&self.score_summary(), TextAlignment::Right);
+            self.state.set(GameState::Transition(center, 0.0));
+            self.text_buffer.set_text(&self.title(), TextAlignment::Left);
+        }
+    }
+
+    fn daily_intro(daily: &DailyChallenge) -> String {
+        match daily.is_completed() {
+            true => format!("Daily Challenge {}\n{}", daily.date(), daily.summary()),
+            false => format!("Daily Challenge {}\nClick the Screen to Start", daily.date())
+        }
+    }
+
+    fn visible_buttons(&self) -> impl Iterator<Item=&(Event, TextBuffer)> {
+        self.buttons
+            .iter()
+            .filter(|(event, _)| match event {
+                Event::Daily => self.main_world.is_none(),
+                Event::Back => self.main_world.is_some(),
+                _ => true
+            })
+    }
+
+    fn score_summary(&self) -> String {
+        let par = self.progress.par.map_or_else(|| String::from("-"), |par| par.to_string());
+        match self.level_key().and_then(|key| self.stats.get(&key)?.best_moves) {
+            Some(best) => format!("Moves {} / Par {}\nBest {}", self.progress.moves, par, best),
+            None => format!("Moves {} / Par {}", self.progress.moves, par)
+        }
+    }
+
+    fn create_button(text_renderer: &TextRenderer, text: &str, anchor: Anchor, offset: Vec2) -> anyhow::Result<TextBuffer> {
+        let mut button = text_renderer.create_buffer()?;
+        button.set_text(text, TextAlignment::Center);
+        button.anchor = anchor;
+        button.text_size = 50.0;
+        button.offset = offset;
+        Ok(button)
+    }
+
+    fn title(&self) -> String {
+        Self::level_title(&self.progression, self.main_world.as_ref().map(|_| &self.daily), &self.world)
+    }
+
+    fn level_title(progression: &Progression, daily: Option<&DailyChallenge>, world: &RenderableWorld) -> String {
+        let name = match (daily, progression) {
+            (Some(daily), _) => format!("Daily {}", daily.date()),
+            (None, Progression::Endless) => format!("Level {}", world.seed()),
+            (None, Progression::Pack { pack, level, .. }) => format!("{} {}/{}", pack.name, level + 1, pack.len())
+        };
+        match world.difficulty() {
+            Some(difficulty) => format!("{} ({})", name, difficulty.label()),
+            None => name
+        }
+    }
+
+}
+
+impl Game for InfinityLoop {
+    type Bundle = InfinityLoopBundle;
+
+    fn resume<A: AppContext>(ctx: &A, bundle: Self::Bundle) -> anyhow::Result<Self> {
+        let renderer = GameRenderer::new(ctx)?;
+
+        let (width, height) = ctx.screen_size();
+        ctx.viewport(0, 0, width as i32, height as i32);
+
+        let camera = Camera {
+            aspect: width as f32 / height as f32,
+            ..bundle.camera
+        }.into();
+
+        let resources = Rc::new(TileRenderResources::new(ctx)?);
+
+        let mut daily = bundle.daily;
+        let mut stats = bundle.stats;
+        stats.import_best(&bundle.best);
+        let mut state = bundle.state;
+        let (world, progress, main_world) = match bundle.daily_active {
+            true => {
+                daily.refresh();
+                // the day might have changed since the game was saved
+                if !daily.is_started() {
+                    state = GameState::Daily;
+                }
+                (daily.world(), daily.progress(), Some((bundle.world, bundle.progress)))
+            },
+            false => (bundle.world, bundle.progress, None)
+        };
+
+        let old_world = RenderableWorld::new(ctx, resources.clone(),
+                                             Self::previous_level(&bundle.progression, main_world.as_ref().map_or(&world, |(world, _)| world)), (width, height))?;
+        let world = RenderableWorld::new(ctx, resources, world, (width, height))?;
+
+        let text_renderer = TextRenderer::new(ctx, &ArteryFont::read(include_bytes!("font/arial.arfont").as_slice())?, (width, height))?;
+        let mut text_buffer = text_renderer.create_buffer()?;
+        match state {
+            GameState::Tutorial | GameState::Shuffeling
+              => text_buffer.set_text("Click the Screen to Start", TextAlignment::Center),
+            GameState::Daily => text_buffer.set_text(&Self::daily_intro(&daily), TextAlignment::Center),
+            GameState::Stats => text_buffer.set_text(&stats.summary(None), TextAlignment::Center),
+            _ => text_buffer.set_text(&Self::level_title(&bundle.progression, main_world.as_ref().map(|_| &daily), &world), TextAlignment::Center),
+        };
+        text_buffer.anchor = Anchor::CenterTop;
+        text_buffer.text_size = 60.0;
+        text_buffer.offset = Vec2::new(0.0, -10.0);
+
+        let hint_button = Self::create_button(&text_renderer, "Hint", Anchor::RightBottom, Vec2::new(-20.0, 20.0))?;
+        let undo_button = Self::create_button(&text_renderer, "Undo", Anchor::LeftBottom, Vec2::new(20.0, 20.0))?;
+        let redo_button = Self::create_button(&text_renderer, "Redo", Anchor::LeftBottom,
+                                              Vec2::new(50.0 + undo_button.size().x, 20.0))?;
+        let daily_button = Self::create_button(&text_renderer, "Daily", Anchor::RightBottom,
+                                               Vec2::new(-50.0 - hint_button.size().x, 20.0))?;
+        let stats_button = Self::create_button(&text_renderer, "Stats", Anchor::RightBottom,
+                                               Vec2::new(-80.0 - hint_button.size().x - daily_button.size().x, 20.0))?;
+        // takes the place of the daily button while the daily challenge is played
+        let back_button = Self::create_button(&text_renderer, "Back", Anchor::RightBottom,
+                                              Vec2::new(-50.0 - hint_button.size().x, 20.0))?;
+        let buttons = vec![
+            (Event::Hint, hint_button),
+            (Event::Undo, undo_button),
+            (Event::Redo, redo_button),
+            (Event::Daily, daily_button),
+            (Event::Stats, stats_button),
+            (Event::Back, back_button)
+        ];
+
+        let mut score_text = text_renderer.create_buffer()?;
+        score_text.anchor = Anchor::RightTop;
+        score_text.offset = Vec2::new(-20.0, -10.0);
+
+        let mut game = Self {
+            renderer,
+            camera,
+            world,
+            old_world,
+            text_renderer,
+            text_buffer,
+            buttons,
+            score_text,
+            state,
+            hints: bundle.hints,
+            progress,
+            stats,
+            // saving on suspend counted the time up to then, so the suspended time is skipped
+            last_activity: Instant::now(),
+            progression: bundle.progression,
+            daily,
+            main_world
+        };
+        game.score_text.set_text(&game.score_summary(), TextAlignment::Right);
+        Ok(game)
+    }
+
+    fn save(&self) -> Self::Bundle {
+        let mut daily = self.daily.clone();
+        let (world, progress) = match &self.main_world {
+            Some((main_world, progress)) => {
+                daily.save_progress((&self.world).into(), self.current_progress());
+                (main_world.clone(), progress.clone())
+            },
+            None => ((&self.world).into(), self.current_progress())
+        };
+        Self::Bundle {
+            world,
+            camera: self.camera.parent,
+            state: self.state,
+            hints: self.hints,
+            progress,
+            best: BTreeMap::new(),
+            stats: self.stats.clone(),
+            progression: self.progression.clone(),
+            daily,
+            daily_active: self.main_world.is_some()
+        }
+    }
+
+    fn event<A: AppContext>(&mut self, ctx: &A, event: Event) -> anyhow::Result<EventResponse> {
+        let mut resp = EventResponse::default();
+        self.progress.level_time = self.current_progress().level_time;
+        self.last_activity = Instant::now();
+        let event = match event {
+            Event::Click(pos, _) if matches!(self.state, GameState::InProgress) => self
+                .visible_buttons()
+                .find(|(_, button)| button.contains(ctx.screen_size(), pos))
+                .map_or(event, |(event, _)| *event),
+            event => event
+        };
+        match event {
+            Event::Draw(delta) => {
+                self.camera.update(delta);
+                let old_state = self.state;
+                self.state.update(delta, self.world.update_required());
+                self.world.update(delta / self.state.update_speed());
+                if matches!(self.state, GameState::Shuffeling) {
+                    self.text_buffer.offset = Vec2::lerp(
+                        self.text_buffer.offset,
+                        self.text_buffer.offset + self.text_buffer.size() * Vec2::new(0.0, 1.0),
+                        1.0 - f32::exp(-3.0 * delta.as_secs_f32()));
+                }
+                if matches!(old_state, GameState::Shuffeling) && matches!(self.state, GameState::InProgress){
+                    self.text_buffer.set_text(&self.title(), TextAlignment::Left);
+                    self.text_buffer.offset = Vec2::new(0.0, -10.0);
+                }
+                if std::mem::discriminant(&old_state) != std::mem::discriminant(&self.state) {
+                    resp.request_save = SaveRequest::Later;
+                }
+
+
+                ctx.clear(Rgba::new(23,23,23,255));
+
+                self.renderer.render(ctx, self.state, &self.camera, &mut self.world, &mut self.old_world)?;
+                self.text_renderer.render(ctx, &self.text_buffer)?;
+                if self.state.is_interactive() {
+                    self.text_renderer.render(ctx, &self.score_text)?;
+                }
+                if matches!(self.state, GameState::InProgress) {
+                    for (_, button) in self.visible_buttons() {
+                        self.text_renderer.render(ctx, button)?;
+                    }
+                }
+            },
+            Event::Resize(width, height) => {
+                assert!(width != 0 && height != 0);
+                ctx.viewport(0, 0, width as i32, height as i32);
+                self.camera.parent.aspect = width as f32 / height as f32;
+                self.world.resize(ctx, width, height)?;
+                self.old_world.resize(ctx, width, height)?;
+                self.text_renderer.resize(ctx, width, height)?;
+                resp.request_redraw = true;
+            },
+            Event::Click(pos, long) => match self.state{
+                GameState::Tutorial => {
+                    //let pt = self.camera.to_world_coords(pos);
+                    //if self.world.try_rotate(pt.into()) {
+                    //    self.text_buffer.set_text(&format!("Level {}", self.world.seed()), TextAlignment::Left);
+                    //    self.state.set(GameState::InProgress);
+                    //}
+                    //if self.world.is_completed() {
+                    //    self.state.set(GameState::WaitingForEnd(pt));
+                    //}
+                    self.world.scramble();
+                    self.start_level();
+                    self.state.set(GameState::Shuffeling);
+                }
+                GameState::InProgress => {
+                    let pt = self.camera.to_world_coords(pos);
+                    let tile = self.world.tile_at(pt);
+                    let direction = Direction::clockwise(!long);
+                    if self.world.try_rotate(tile, direction) {
+                        self.progress.history.push(tile, direction);
+                        self.record_move();
+                        resp.request_save = SaveRequest::Later;
+                    }
+                    self.check_completion(pt);
+                }
+                GameState::Ending(_, _) => {
+                    self.state.set(GameState::Ended);
+                    resp.request_redraw = true;
+                },
+                GameState::Daily => {
+                    if self.daily.is_completed() {
+                        self.leave_daily(self.camera.to_world_coords(pos));
+                    } else {
+                        if self.daily.is_started() {
+                            self.state.set(GameState::InProgress);
+                            self.text_buffer.set_text(&self.title(), TextAlignment::Left);
+                        } else {
+                            self.world.scramble();
+                            self.daily.start();
+                            self.state.set(GameState::Shuffeling);
+                            self.start_level();
+                        }
+                    }
+                    resp.request_save = SaveRequest::Later;
+                    resp.request_redraw = true;
+                },
+                GameState::Stats => {
+                    self.state.set(GameState::InProgress);
+                    self.text_buffer.set_text(&self.title(), TextAlignment::Left);
+                    resp.request_redraw = true;
+                },
+                GameState::Ended if self.main_world.is_some() => {
+                    self.leave_daily(self.camera.to_world_coords(pos));
+                    resp.request_save = SaveRequest::Now;
+                    resp.request_redraw = true;
+                },
+                GameState::Ended => {
+                    let pt = self.camera.to_world_coords(pos);
+                    let mut new_world = self.next_level();
+                    let scramble = ScrambleParams::default()
+                        .with_min_misplaced_percent(Self::MIN_MISPLACED_PERCENT);
+                    if let Err(err) = new_world.scramble(scramble) {
+                        log::warn!("{:#}", err);
+                    }
+                    std::mem::swap(&mut self.world, &mut self.old_world);
+                    self.world.reinitialize(new_world);
+                    self.start_level();
+                    self.state.set(GameState::Transition(pt, 0.0));
+                    self.text_buffer.set_text(&self.title(), TextAlignment::Left);
+                    resp.request_save = SaveRequest::Now;
+                    resp.request_redraw = true;
+                },
+                GameState::Transition(_, _) => {
+                    self.state.set(GameState::InProgress);
+                    resp.request_redraw = true;
+                }
+                _ => {}
+            },
+            Event::Zoom(center, amount, animate) => if self.state.is_interactive() {
+                self.camera.zoom(center, amount, animate);
+                resp.request_redraw = true;
+            }
+            Event::Drag(delta) => if self.state.is_interactive() {
+                self.camera.move_by(self.camera.to_world_coords(-delta) - self.camera.to_world_coords(Vec2::ZERO));
+                resp.request_redraw = true;
+            }
+            Event::TouchStart => if self.state.is_interactive() {
+                self.camera.capture()
+            },
+            Event::TouchEnd => if self.state.is_interactive() {
+                self.camera.release()
+            }
+            Event::Hint => if matches!(self.state, GameState::InProgress) && self.show_hint() {
+                resp.request_save = SaveRequest::Later;
+            }
+            Event::Undo => if matches!(self.state, GameState::InProgress) && self.progress.history.undo().is_some_and(|m| self.replay(m)) {
+                resp.request_save = SaveRequest::Later;
+            }
+            Event::Redo => if matches!(self.state, GameState::InProgress) && self.progress.history.redo().is_some_and(|m| self.replay(m)) {
+                resp.request_save = SaveRequest::Later;
+            }
+            Event::Daily => if matches!(self.state, GameState::InProgress) && self.main_world.is_none() {
+                self.enter_daily();
+                resp.request_save = SaveRequest::Later;
+                resp.request_redraw = true;
+            }
+            Event::Stats => if matches!(self.state, GameState::InProgress) {
+                self.show_stats();
+                resp.request_redraw = true;
+            }
+            Event::Back => if matches!(self.state, GameState::InProgress | GameState::Daily) && self.main_world.is_some() {
+                self.leave_daily(self.camera.to_world_coords(Vec2::splat(0.5)));
+                resp.request_save = SaveRequest::Later;
+                resp.request_redraw = true;
+            }
+        }
+        resp.request_redraw |= self.camera.update_required() || self.world.update_required() || self.state.is_animated();
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_progress() {
+        let mut bundle = InfinityLoopBundle::default();
+        bundle.progress.moves = 7;
+        bundle.progress.par = Some(5);
+        bundle.progress.level_hints = 2;
+        bundle.progress.level_time = Duration::from_secs(90);
+        bundle.progress.history.push(HexPos::CENTER, Direction::CW);
+        let json = serde_json::to_value(&bundle).unwrap();
+        // the progress is stored under the field names of older saves
+        assert_eq!(json["moves"], 7);
+        assert_eq!(json["par"], 5);
+        let mut restored: InfinityLoopBundle = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.progress.moves, 7);
+        assert_eq!(restored.progress.par, Some(5));
+        assert_eq!(restored.progress.level_hints, 2);
+        assert_eq!(restored.progress.level_time, Duration::from_secs(90));
+        assert_eq!(restored.progress.history.undo(), Some((HexPos::CENTER, Direction::CCW)));
+    }
+
+}
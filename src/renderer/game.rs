@@ -1,12 +1,17 @@
 use std::time::Duration;
 use glam::Vec2;
 use serde::{Serialize, Deserialize};
-use crate::{AppContext, Camera, RenderableWorld};
+use crate::app::AppContext;
+use crate::camera::Camera;
+use crate::renderer::RenderableWorld;
 use crate::opengl::*;
+use crate::world::MAX_COLORS;
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameState {
     Tutorial,
+    Daily,
+    Stats,
     Shuffeling,
     InProgress,
     WaitingForEnd(Vec2),
@@ -62,11 +67,35 @@ impl GameState {
     }
 
     pub fn is_interactive(&self) -> bool {
-        !matches!(self, GameState::Shuffeling | GameState::Tutorial )
+        !matches!(self, GameState::Shuffeling | GameState::Tutorial | GameState::Daily | GameState::Stats)
     }
 
 }
 
+// the pipe color of every color index
+const PALETTE: [[f32; 3]; 4] = [
+    [0.847, 0.871, 0.914],
+    [0.749, 0.380, 0.416],
+    [0.533, 0.753, 0.816],
+    [0.639, 0.745, 0.549]
+];
+
+const _: () = assert!(PALETTE.len() == MAX_COLORS as usize, "every color needs an entry in the palette");
+
+// inserts the palette and the shared pipe coloring after the precision declaration of a postprocess shader
+fn with_palette(source: &str) -> String {
+    let precision = "precision highp float;\n";
+    let (header, body) = source
+        .split_once(precision)
+        .expect("postprocess shaders have to declare the float precision");
+    let colors: Vec<String> = PALETTE
+        .iter()
+        .map(|[r, g, b]| format!("vec4({:?}, {:?}, {:?}, 1.0)", r, g, b))
+        .collect();
+    format!("{}{}const vec4 palette[{n}] = vec4[{n}]({});\n{}{}", header, precision, colors.join(", "),
+            include_str!("../shader/palette.glsl"), body, n = PALETTE.len())
+}
+
 pub struct GameRenderer {
     standard_shader: ShaderProgram,
     ending_shader: ShaderProgram,
@@ -77,9 +106,9 @@ impl GameRenderer {
 
     pub fn new(ctx: &Context) -> GlResult<Self> {
         let vertex = Shader::new(ctx, ShaderType::Vertex, include_str!("../shader/postprocess.vert"))?;
-        let standard_fragment = Shader::new(ctx, ShaderType::Fragment, include_str!("../shader/postprocess_standard.frag"))?;
-        let ending_fragment = Shader::new(ctx, ShaderType::Fragment, include_str!("../shader/postprocess_ending.frag"))?;
-        let transition_fragment = Shader::new(ctx, ShaderType::Fragment, include_str!("../shader/postprocess_transition.frag"))?;
+        let standard_fragment = Shader::new(ctx, ShaderType::Fragment, &with_palette(include_str!("../shader/postprocess_standard.frag")))?;
+        let ending_fragment = Shader::new(ctx, ShaderType::Fragment, &with_palette(include_str!("../shader/postprocess_ending.frag")))?;
+        let transition_fragment = Shader::new(ctx, ShaderType::Fragment, &with_palette(include_str!("../shader/postprocess_transition.frag")))?;
 
         let standard_shader = ShaderProgram::new(ctx, &[&vertex, &standard_fragment])?;
         ctx.use_program(&standard_shader);
@@ -105,7 +134,7 @@ impl GameRenderer {
         world.render(ctx, camera);
         ctx.bind_texture(0, world.get_texture());
         match state {
-            GameState::Tutorial | GameState::Shuffeling | GameState::InProgress | GameState::WaitingForEnd(_) => {
+            GameState::Tutorial | GameState::Daily | GameState::Stats | GameState::Shuffeling | GameState::InProgress | GameState::WaitingForEnd(_) => {
                 ctx.use_program(&self.standard_shader);
                 ctx.set_uniform(&self.standard_shader.get_uniform("completed")?, false);
             }
@@ -1,6 +1,6 @@
 use glam::Vec2;
 use sdf2d::{Constant, Ops, Sdf, Shapes};
-use crate::Camera;
+use crate::camera::Camera;
 use crate::opengl::*;
 use crate::world::TileType;
 
@@ -46,7 +46,7 @@ impl TileRenderResources {
 
 
 fn generate_tile_texture(ctx: &Context) -> GlResult<Texture> {
-    let mut builder = ArrayTextureBuilder::new(ctx, TILE_RES, TILE_RES, 8, -TILE_RANGE)?;
+    let mut builder = ArrayTextureBuilder::new(ctx, TILE_RES, TILE_RES, 19, -TILE_RANGE)?;
 
     let a = 0.75;
     let g = 0.75 * f32::tan(f32::to_radians(30.0));
@@ -116,6 +116,83 @@ fn generate_tile_texture(ctx: &Context) -> GlResult<Texture> {
         .intersection(hexagon);
     builder.fill_layer(TileType::Tile0134.model() as u32, tile0134);
 
+    // an arc around the corner at the given angle, connecting the two sides next to it
+    let corner_arc = |angle: f32| {
+        let c = a / f32::cos(f32::to_radians(30.0));
+        let angle = f32::to_radians(angle);
+        Shapes::circle(g + 0.1)
+            .subtract(Shapes::circle(g - 0.1))
+            .translate(c * f32::cos(angle), c * f32::sin(angle))
+    };
+
+    let tile013 = Shapes::rectangle(0.1, 0.75)
+        .rotate(f32::to_radians(210.0))
+        .union(corner_arc(-30.0))
+        .intersection(hexagon);
+    builder.fill_layer(TileType::Tile013.model() as u32, tile013);
+
+    let tile014 = Shapes::rectangle(0.75, 0.1)
+        .union(corner_arc(-30.0))
+        .intersection(hexagon);
+    builder.fill_layer(TileType::Tile014.model() as u32, tile014);
+
+    let tile0123 = Constant::Empty
+        .union(corner_arc(-30.0))
+        .union(corner_arc(30.0))
+        .union(corner_arc(90.0))
+        .intersection(hexagon);
+    builder.fill_layer(TileType::Tile0123.model() as u32, tile0123);
+
+    let tile0124 = Shapes::rectangle(0.75, 0.1)
+        .union(corner_arc(-30.0))
+        .union(corner_arc(30.0))
+        .intersection(hexagon);
+    builder.fill_layer(TileType::Tile0124.model() as u32, tile0124);
+
+    let tile01234 = Constant::Empty
+        .union(corner_arc(-30.0))
+        .union(corner_arc(30.0))
+        .union(corner_arc(90.0))
+        .union(corner_arc(150.0))
+        .intersection(hexagon);
+    builder.fill_layer(TileType::Tile01234.model() as u32, tile01234);
+
+    let tile012345 = Constant::Empty
+        .union(corner_arc(-30.0))
+        .union(corner_arc(30.0))
+        .union(corner_arc(90.0))
+        .union(corner_arc(150.0))
+        .union(corner_arc(210.0))
+        .union(corner_arc(270.0))
+        .intersection(hexagon);
+    builder.fill_layer(TileType::Tile012345.model() as u32, tile012345);
+
+    let square = Shapes::rectangle(a, a);
+
+    let square0 = Shapes::circle(0.45)
+        .subtract(Shapes::circle(0.25))
+        .union(Shapes::rectangle(0.1, 0.25)
+            .translate(0.0, -0.5));
+    builder.fill_layer(TileType::Square0.model() as u32, square0);
+
+    let square01 = Shapes::circle(a + 0.1)
+        .subtract(Shapes::circle(a - 0.1))
+        .translate(a, -a)
+        .intersection(square);
+    builder.fill_layer(TileType::Square01.model() as u32, square01);
+
+    let square02 = Shapes::rectangle(0.1, a);
+    builder.fill_layer(TileType::Square02.model() as u32, square02);
+
+    let square012 = Shapes::rectangle(0.1, a)
+        .union(Shapes::rectangle(0.5 * a, 0.1)
+            .translate(0.5 * a, 0.0));
+    builder.fill_layer(TileType::Square012.model() as u32, square012);
+
+    let square0123 = Shapes::rectangle(0.1, a)
+        .union(Shapes::rectangle(a, 0.1));
+    builder.fill_layer(TileType::Square0123.model() as u32, square0123);
+
     Ok(builder.finalize())
 }
 
@@ -211,6 +211,13 @@ impl TextBuffer {
         self.offset + (screen_size - self.size()) * Vec2::from(self.anchor)
     }
 
+    pub fn contains(&self, screen_size: (u32, u32), pos: Vec2) -> bool {
+        let pos = pos * Vec2::new(screen_size.0 as f32, screen_size.1 as f32);
+        let min = self.position(screen_size);
+        let max = min + self.size();
+        pos.cmpge(min).all() && pos.cmple(max).all()
+    }
+
 }
 
 pub struct TextRenderer {
@@ -3,12 +3,12 @@ use std::time::Duration;
 use bytemuck::{Pod, Zeroable};
 use glam::{Mat3, Vec2};
 use hashbrown::HashSet;
-use crate::{Camera, Color, HexPos};
+use crate::camera::Camera;
 use crate::opengl::*;
 use crate::renderer::TileRenderResources;
-use crate::types::Angle;
+use crate::types::{Angle, Color, HexPos, Topology};
 use crate::util::OptionExt;
-use crate::world::{Direction, HexMap, TileConfig, World};
+use crate::world::{Difficulty, Direction, HexMap, ScrambleParams, TileConfig, World};
 
 pub struct RenderableWorld {
     resources: Rc<TileRenderResources>,
@@ -19,6 +19,7 @@ pub struct RenderableWorld {
     last_camera: Option<Camera>,
     world: World,
     instances: HexMap<RenderState>,
+    ghosts: Vec<(HexPos, Vec2)>,
     active_instances: HashSet<HexPos>
 }
 
@@ -33,16 +34,21 @@ impl RenderableWorld {
             VertexArrayAttribute::Float(0, DataType::F32, 3, false),
             VertexArrayAttribute::Float(1, DataType::F32, 3, false),
             VertexArrayAttribute::Float(2, DataType::F32, 3, false),
-            VertexArrayAttribute::Integer(3, DataType::U32, 1)
+            VertexArrayAttribute::Integer(3, DataType::U32, 1),
+            VertexArrayAttribute::Integer(4, DataType::U32, 1),
+            VertexArrayAttribute::Integer(5, DataType::U32, 1),
+            VertexArrayAttribute::Integer(6, DataType::U32, 1),
+            VertexArrayAttribute::Integer(7, DataType::U32, 1)
         ]);
 
-        let framebuffer_dst = Texture::new(ctx, TextureType::Texture2d(width, height), InternalFormat::R8, MipmapLevels::None)?;
+        let framebuffer_dst = Texture::new(ctx, TextureType::Texture2d(width, height), InternalFormat::Rg8, MipmapLevels::None)?;
         let framebuffer = Framebuffer::new(ctx, &[
             (FramebufferAttachment::Color(0), &framebuffer_dst)
         ])?;
 
 
-        let instances = HexMap::new(world.tiles().radius());
+        let instances = HexMap::from(world.tiles(), |_| RenderState::default());
+        let ghosts = Self::ghosts(&world);
 
         let mut renderer = Self {
             resources,
@@ -53,6 +59,7 @@ impl RenderableWorld {
             last_camera: None,
             world,
             instances,
+            ghosts,
             active_instances: HashSet::new()
         };
         renderer.reset();
@@ -61,7 +68,7 @@ impl RenderableWorld {
 
     pub fn resize(&mut self, ctx: &Context, width: u32, height: u32) -> anyhow::Result<()> {
         self.framebuffer_dst = Texture::new(ctx, TextureType::Texture2d(width, height),
-                                            InternalFormat::R8, MipmapLevels::None)?;
+                                            InternalFormat::Rg8, MipmapLevels::None)?;
         self.framebuffer.update_attachments(&[(FramebufferAttachment::Color(0), &self.framebuffer_dst)])?;
         self.last_camera = None;
         Ok(())
@@ -69,23 +76,66 @@ impl RenderableWorld {
 
     fn reset(&mut self){
         debug_assert_eq!(self.instances.len(), self.world.tiles().len());
+        let topology = self.world.topology();
         for (pos, tc) in self.world.iter() {
-            self.instances[pos] = RenderState::new(pos, tc);
+            self.instances[pos] = RenderState::new(topology.to_point(pos), tc, self.world.is_locked(pos));
+        }
+        for pos in Self::closed_tiles(&self.world) {
+            self.instances[pos].closed = true;
         }
 
-        let instance_data = self.instances.values().map(RenderState::as_instance).collect::<Vec<Instance>>();
+        let instance_data = self.instances
+            .values()
+            .map(RenderState::as_instance)
+            .chain(self.ghosts.iter().map(|(pos, offset)| self.instances[*pos].as_ghost_instance(*offset)))
+            .collect::<Vec<Instance>>();
         self.instance_buffer.set_data(instance_data.as_slice(), BufferUsage::DynamicDraw);
         self.last_camera = None;
     }
 
     pub fn reinitialize(&mut self, world: World) {
-        if self.instances.len() != world.tiles().len() {
-            self.instances = HexMap::new(world.tiles().radius());
-        }
+        self.instances = HexMap::from(world.tiles(), |_| RenderState::default());
+        self.ghosts = Self::ghosts(&world);
         self.world = world;
         self.reset()
     }
 
+    // copies of the tiles from the opposite side of a wrapping board that are drawn around its border
+    fn ghosts(world: &World) -> Vec<(HexPos, Vec2)> {
+        let tiles = world.tiles();
+        if !tiles.wraps() {
+            return Vec::new();
+        }
+        let topology = tiles.topology();
+        topology
+            .wrap_offsets(tiles.radius())
+            .flat_map(|offset| tiles.keys().map(move |pos| (pos, pos + offset)))
+            .filter(|(_, ghost)| topology.neighbors(*ghost).any(|n| tiles.contains(n)))
+            .map(|(pos, ghost)| (pos, topology.to_point(ghost) - topology.to_point(pos)))
+            .collect()
+    }
+
+    fn closed_tiles(world: &World) -> HashSet<HexPos> {
+        world
+            .networks()
+            .into_iter()
+            .filter(|network| network.closed)
+            .flat_map(|network| network.tiles)
+            .collect()
+    }
+
+    // tints the tiles of every network that got closed or opened by the last move
+    fn update_networks(&mut self) {
+        let closed = Self::closed_tiles(&self.world);
+        for pos in self.world.tiles().keys() {
+            let instance = &mut self.instances[pos];
+            if instance.closed != closed.contains(&pos) {
+                instance.closed = !instance.closed;
+                self.active_instances.insert(pos);
+            }
+        }
+    }
+
     pub fn get_texture(&self) -> &Texture {
         &self.framebuffer_dst
     }
@@ -101,14 +151,13 @@ impl RenderableWorld {
             });
             self.resources.prepare(ctx, camera);
             ctx.use_vertex_array(&self.vertex_array);
-            ctx.draw_arrays_instanced(PrimitiveType::TriangleStrip, 0, 4, self.instances.len() as i32);
+            ctx.draw_arrays_instanced(PrimitiveType::TriangleStrip, 0, 4, (self.instances.len() + self.ghosts.len()) as i32);
             self.last_camera = Some(*camera);
         }
 
     }
 
     pub fn update(&mut self, delta: Duration) {
-        self.active_instances.retain(|pos| self.instances[*pos].update_required());
         for pos in self.active_instances.iter().copied() {
             let offset = self.instances.index(pos).unwrap();
             let instance = &mut self.instances[pos];
@@ -116,6 +165,13 @@ impl RenderableWorld {
             self.instance_buffer.set_sub_data(offset, &[instance.as_instance()]);
             self.last_camera = None;
         }
+        for (i, (pos, offset)) in self.ghosts.iter().enumerate() {
+            if self.active_instances.contains(pos) {
+                let instance = self.instances[*pos].as_ghost_instance(*offset);
+                self.instance_buffer.set_sub_data(self.instances.len() + i, &[instance]);
+            }
+        }
+        self.active_instances.retain(|pos| self.instances[*pos].update_required());
 
     }
 
@@ -124,13 +180,16 @@ impl RenderableWorld {
     }
 
     pub fn scramble(&mut self) {
-        self.world.scramble(true);
+        if let Err(err) = self.world.scramble(ScrambleParams::default().with_force_rotation(true)) {
+            log::warn!("{:#}", err);
+        }
         for pos in self.world.tiles().keys() {
             let tc = self.world.tiles()[pos];
             self.instances[pos].update_target_rotation(tc.angle());
 
             self.active_instances.insert(pos);
         }
+        self.update_networks();
     }
 
     pub fn try_rotate(&mut self, pos: HexPos, direction: Direction) -> bool {
@@ -140,19 +199,53 @@ impl RenderableWorld {
             self.instances[pos].update_target_rotation(tc.angle());
 
             self.active_instances.insert(pos);
+            self.update_networks();
         }
 
         result
     }
 
+    // returns the revealed tile together with the rotations that were applied to it
+    pub fn hint(&mut self) -> Option<(HexPos, Direction, u8)> {
+        let (pos, target) = self.world.hint()?;
+        let current = self.world.tiles()[pos];
+        let steps = current.distance(target)?;
+        let direction = match current.rotate_by(steps).edges() == target.edges() {
+            true => Direction::CW,
+            false => Direction::CCW
+        };
+        for _ in 0..steps {
+            self.try_rotate(pos, direction);
+        }
+        self.instances[pos].highlight();
+        self.active_instances.insert(pos);
+        Some((pos, direction, steps))
+    }
+
     pub fn is_completed(&self) -> bool {
         self.world.is_completed()
     }
 
+    pub fn topology(&self) -> Topology {
+        self.world.topology()
+    }
+
+    pub fn tile_at(&self, pt: Vec2) -> HexPos {
+        self.world.tiles().wrap(self.topology().from_point(pt))
+    }
+
     pub fn seed(&self) -> u64 {
         self.world.seed()
     }
 
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.world.difficulty()
+    }
+
+    pub fn par(&self) -> Option<u32> {
+        self.world.par()
+    }
+
 }
 
 impl From<RenderableWorld> for World {
@@ -172,7 +265,28 @@ impl From<&RenderableWorld> for World {
 #[repr(C)]
 struct Instance {
     model: Mat3,
-    texture: u32
+    texture: u32,
+    locked: u32,
+    color: u32,
+    ghost: u32,
+    closed: u32
+}
+
+const HIGHLIGHT_DURATION: f32 = 0.8;
+
+// four bits per side of the unrotated tile, 15 marks closed sides, and the number of sides above them
+fn packed_colors(config: TileConfig) -> u32 {
+    match config {
+        TileConfig::Empty => 0,
+        TileConfig::Tile(t, _, colors) => t.endings()[..t.sides()]
+            .iter()
+            .zip(colors)
+            .enumerate()
+            .fold((t.sides() as u32) << 24, |acc, (side, (open, color))| {
+                let color = if *open { color as u32 } else { 15 };
+                acc | color << (4 * side)
+            })
+    }
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -180,33 +294,56 @@ struct RenderState {
     pos: Vec2,
     scale: f32,
     texture: u32,
+    locked: bool,
+    colors: u32,
+    closed: bool,
     current_rotation: Angle,
-    target_rotation: Angle
+    target_rotation: Angle,
+    highlight: f32
 }
 
 impl RenderState {
 
-    fn new(pos: HexPos, config: TileConfig) -> Self {
+    fn new(pos: Vec2, config: TileConfig, locked: bool) -> Self {
         Self {
-            pos: pos.into(),
+            pos,
             scale: match config {
                 TileConfig::Empty => 0.0,
-                TileConfig::Tile(_, _) => 1.155,
+                TileConfig::Tile(..) => 1.155,
             },
             texture: config.model() as u32,
+            locked,
+            colors: packed_colors(config),
+            closed: false,
             current_rotation: config.angle(),
-            target_rotation: config.angle()
+            target_rotation: config.angle(),
+            highlight: 0.0
         }
     }
 
     fn as_instance(&self) -> Instance {
         Instance {
             model: Mat3::from_scale_angle_translation(
-                Vec2::ONE * self.scale,
+                Vec2::ONE * self.scale * self.highlight_scale(),
                 self.current_rotation.to_radians(),
                 self.pos
             ),
-            texture: self.texture
+            texture: self.texture,
+            locked: self.locked as u32,
+            color: self.colors,
+            ghost: 0,
+            closed: self.closed as u32
+        }
+    }
+
+    fn as_ghost_instance(&self, offset: Vec2) -> Instance {
+        let ghost = Self {
+            pos: self.pos + offset,
+            ..*self
+        };
+        Instance {
+            ghost: 1,
+            ..ghost.as_instance()
         }
     }
 
@@ -214,10 +351,19 @@ impl RenderState {
         self.current_rotation = Angle::lerp_snap(self.current_rotation, self.target_rotation,
                                                  1.0 - f32::exp(-14.0 * delta.as_secs_f32()),
                                                  Angle::radians(0.03));
+        self.highlight = f32::max(self.highlight - delta.as_secs_f32(), 0.0);
     }
 
     fn update_required(&self) -> bool {
-        self.current_rotation != self.target_rotation
+        self.current_rotation != self.target_rotation || self.highlight > 0.0
+    }
+
+    fn highlight(&mut self) {
+        self.highlight = HIGHLIGHT_DURATION;
+    }
+
+    fn highlight_scale(&self) -> f32 {
+        1.0 + 0.2 * f32::sin(std::f32::consts::PI * self.highlight / HIGHLIGHT_DURATION)
     }
 
     fn update_target_rotation(&mut self, target: Angle) {
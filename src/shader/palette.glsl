@@ -0,0 +1,10 @@
+// shared by the postprocess shaders, the palette in front of it is generated from the colors in game.rs
+
+const vec4 closed_tint = vec4(0.922,0.796,0.545,1.0);
+
+vec4 pipe_color(float g) {
+    int v = int(round(g * 255.0));
+    vec4 color = palette[clamp((v & 127) - 1, 0, palette.length() - 1)];
+    return v >= 128 ? mix(color, closed_tint, 0.4) : color;
+}
+
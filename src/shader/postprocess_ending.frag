@@ -12,7 +12,6 @@ uniform float radius;
 uniform float pxRange;
 uniform vec2 center;
 
-vec4 foreground  = vec4(0.847,0.871,0.914,1.0);
 vec4 background1 = vec4(0.231,0.259,0.322,1.0);
 vec4 background2 = vec4(0.180,0.204,0.251,1.0);
 
@@ -27,13 +26,14 @@ float opSmoothSubtraction( float d1, float d2, float k ) {
 }
 
 void main() {
-    float sd = (texture(tex, tex_coords).r - 0.5) * 10.0;
+    vec2 value = texture(tex, tex_coords).rg;
+    float sd = (value.r - 0.5) * 10.0;
 
     float f = length(world_pos - center) - radius;
 
     float final_opacity = abs(min(opSmoothSubtraction(sd, (f + 0.1) * pxRange, 12.0), opSmoothSubtraction(sd, -(f - 0.1) * pxRange, 12.0))) - 0.25;
     final_opacity = abs(final_opacity) - 0.15;
 
-    finalColor = mix(mix(background1, background2, smoothstep(-0.1, 0.1, f)), foreground, 1.0 - clamp(final_opacity, 0.0, 1.0));
+    finalColor = mix(mix(background1, background2, smoothstep(-0.1, 0.1, f)), pipe_color(value.g), 1.0 - clamp(final_opacity, 0.0, 1.0));
 
 }
\ No newline at end of file
@@ -13,7 +13,6 @@ uniform float radius;
 uniform float pxRange;
 uniform vec2 center;
 
-vec4 foreground  = vec4(0.847,0.871,0.914,1.0);
 vec4 background1 = vec4(0.231,0.259,0.322,1.0);
 vec4 background2 = vec4(0.180,0.204,0.251,1.0);
 
@@ -33,15 +32,19 @@ float opSmoothUnion( float d1, float d2, float k ) {
 }
 
 void main() {
-    float sd1 = (texture(tex1, tex_coords).r - 0.5) * 10.0;
+    vec2 value1 = texture(tex1, tex_coords).rg;
+    float sd1 = (value1.r - 0.5) * 10.0;
 
-    float sd2 = (texture(tex2, tex_coords).r - 0.5) * 10.0;
+    vec2 value2 = texture(tex2, tex_coords).rg;
+    float sd2 = (value2.r - 0.5) * 10.0;
 
     float f = length(world_pos - center) - radius;
 
     float final_opacity = abs(min(opSmoothSubtraction(sd1, (f + 0.1) * pxRange, 12.0), opSmoothSubtraction(sd2, -(f - 0.1) * pxRange, 12.0))) - 0.25;
     final_opacity = abs(final_opacity) - 0.15;
 
+    vec4 foreground = pipe_color(f < 0.0 ? value1.g : value2.g);
+
     finalColor = mix(mix(background2, background1, smoothstep(-0.1, 0.1, f)), foreground, 1.0 - clamp(final_opacity, 0.0, 1.0));
 
 }
\ No newline at end of file
@@ -3,21 +3,60 @@
 precision highp float;
 precision highp sampler2DArray;
 
-out float finalColor;
+out vec2 finalColor;
 
 in vec3 tex_coords;
+flat in uint tile_locked;
+flat in uint tile_color;
+flat in uint tile_ghost;
+flat in uint tile_closed;
 
 uniform sampler2DArray tex;
 uniform float range;
 
+// locked tiles are drawn with noticeably thicker pipes
+const float locked_offset = 0.09;
+// the copies around wrapping boards are drawn thinner
+const float ghost_offset = 0.06;
+
 float screenPxRange() {
     vec2 unitRange = vec2(range);
     vec2 screenTexSize = vec2(1.0)/ vec2(length(dFdx(tex_coords.xy)), length(dFdy(tex_coords.xy)));
     return max(0.5*dot(unitRange, screenTexSize), 1.0);
 }
 
+// the color of the ending closest to the fragment, the colors are packed into four bits per side of the
+// unrotated tile with 15 for closed sides and the number of sides in the highest byte
+uint ending_color() {
+    uint sides = tile_color >> 24;
+    vec2 p = vec2(tex_coords.x * 2.0 - 1.0, 1.0 - tex_coords.y * 2.0);
+    float position = atan(p.y, p.x) / 6.28318530718 * float(sides);
+    float half_turn = 0.5 * float(sides);
+    uint color = 0u;
+    float closest = float(sides);
+    for (uint side = 0u; side < sides; side++) {
+        uint c = (tile_color >> (4u * side)) & 15u;
+        float offset = abs(mod(position - float(side) + half_turn, float(sides)) - half_turn);
+        if (c != 15u && offset < closest) {
+            closest = offset;
+            color = c;
+        }
+    }
+    return color;
+}
+
 void main() {
     float sd = texture(tex, tex_coords).r - 0.5;
+    if (tile_locked != 0u) {
+        sd += locked_offset;
+    }
+    if (tile_ghost != 0u) {
+        sd -= ghost_offset;
+    }
     float screenPxDistance = screenPxRange() * (1.0 / 10.0) * sd;
-    finalColor = screenPxDistance + 0.5;
+    float value = screenPxDistance + 0.5;
+    // the color index is stored offset by one so that the cleared background reads as no pipe,
+    // the highest bit marks tiles of closed networks
+    uint index = (ending_color() + 1u) | (tile_closed != 0u ? 128u : 0u);
+    finalColor = vec2(value, value > 0.3 ? float(index) / 255.0 : 0.0);
 }
@@ -2,6 +2,10 @@
 
 layout(location = 0) in mat3 model;
 layout(location = 3) in uint texId;
+layout(location = 4) in uint locked;
+layout(location = 5) in uint color;
+layout(location = 6) in uint ghost;
+layout(location = 7) in uint closed;
 
 const vec2 vertex_positions[4] = vec2[4](
     vec2(-1., -1.),
@@ -14,10 +18,18 @@ const vec2 vertex_positions[4] = vec2[4](
 uniform mat3 camera;
 
 out vec3 tex_coords;
+flat out uint tile_locked;
+flat out uint tile_color;
+flat out uint tile_ghost;
+flat out uint tile_closed;
 
 void main() {
     vec2 vertex_position = vertex_positions[gl_VertexID];
     tex_coords = vec3((vertex_position + vec2(1., 1.)) * 0.5, float(texId));
+    tile_locked = locked;
+    tile_color = color;
+    tile_ghost = ghost;
+    tile_closed = closed;
     vec3 position = camera * model * vec3(vertex_position, 1);
     gl_Position = vec4(position.xy / position.z, 0, 1);
 
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use crate::daily::{format_day, DailyChallenge};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LevelKey {
+    Endless(u64),
+    Pack(String, usize)
+}
+
+impl LevelKey {
+
+    fn name(&self) -> String {
+        match self {
+            LevelKey::Endless(seed) => format!("Level {}", seed),
+            LevelKey::Pack(pack, level) => format!("{} {}", pack, level + 1)
+        }
+    }
+
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct Solve {
+    // in seconds
+    pub time: u64,
+    pub moves: u32,
+    pub hints: u32,
+    // days since the unix epoch
+    pub day: u64
+}
+
+impl Solve {
+
+    pub fn new(time: u64, moves: u32, hints: u32) -> Self {
+        Self {
+            time,
+            moves,
+            hints,
+            day: DailyChallenge::today()
+        }
+    }
+
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LevelStats {
+    pub solves: u32,
+    pub total_time: u64,
+    pub total_moves: u32,
+    pub total_hints: u32,
+    pub last: Option<Solve>,
+    pub best_time: Option<u64>,
+    pub best_moves: Option<u32>
+}
+
+impl LevelStats {
+
+    fn record(&mut self, solve: Solve) {
+        self.solves += 1;
+        self.total_time += solve.time;
+        self.total_moves += solve.moves;
+        self.total_hints += solve.hints;
+        self.last = Some(solve);
+        self.best_time = Some(self.best_time.map_or(solve.time, |best| best.min(solve.time)));
+        self.best_moves = Some(self.best_moves.map_or(solve.moves, |best| best.min(solve.moves)));
+    }
+
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Stats {
+    #[serde(default)]
+    endless: BTreeMap<u64, LevelStats>,
+    #[serde(default)]
+    packs: BTreeMap<String, BTreeMap<usize, LevelStats>>
+}
+
+impl Stats {
+
+    pub fn get(&self, key: &LevelKey) -> Option<&LevelStats> {
+        match key {
+            LevelKey::Endless(seed) => self.endless.get(seed),
+            LevelKey::Pack(pack, level) => self.packs.get(pack)?.get(level)
+        }
+    }
+
+    pub fn record(&mut self, key: LevelKey, solve: Solve) {
+        let stats = match key {
+            LevelKey::Endless(seed) => self.endless.entry(seed).or_default(),
+            LevelKey::Pack(pack, level) => self.packs.entry(pack).or_default().entry(level).or_default()
+        };
+        stats.record(solve);
+        log::info!("Solved level in {}s with {} moves and {} hints", solve.time, solve.moves, solve.hints);
+    }
+
+    // older saves only kept the fewest moves for every endless level
+    pub fn import_best(&mut self, best: &BTreeMap<u64, u32>) {
+        for (seed, moves) in best {
+            let stats = self.endless.entry(*seed).or_default();
+            stats.best_moves.get_or_insert(*moves);
+        }
+    }
+
+    fn levels(&self) -> impl Iterator<Item=(LevelKey, &LevelStats)> {
+        self.endless
+            .iter()
+            .map(|(seed, stats)| (LevelKey::Endless(*seed), stats))
+            .chain(self.packs
+                .iter()
+                .flat_map(|(pack, levels)| levels
+                    .iter()
+                    .map(|(level, stats)| (LevelKey::Pack(pack.clone(), *level), stats))))
+    }
+
+    pub fn summary(&self, current: Option<&LevelKey>) -> String {
+        // imported entries from older saves only have a best move count
+        let levels = self.levels().filter(|(_, stats)| stats.solves > 0).count();
+        let solves: u32 = self.levels().map(|(_, stats)| stats.solves).sum();
+        let time: u64 = self.levels().map(|(_, stats)| stats.total_time).sum();
+        let moves: u32 = self.levels().map(|(_, stats)| stats.total_moves).sum();
+        let hints: u32 = self.levels().map(|(_, stats)| stats.total_hints).sum();
+
+        let mut lines = vec![
+            String::from("Statistics"),
+            format!("Levels Solved {} / Solves {}", levels, solves),
+            format!("Total Time {} / Moves {} / Hints {}", format_time(time), moves, hints)
+        ];
+        if solves > 0 {
+            lines.push(format!("Average Time {} / Moves {}", format_time(time / solves as u64), moves / solves));
+        }
+        let fastest = self.levels()
+            .filter_map(|(key, stats)| Some((stats.best_time?, key)))
+            .min_by_key(|(time, _)| *time);
+        if let Some((time, key)) = fastest {
+            lines.push(format!("Fastest Solve {} ({})", format_time(time), key.name()));
+        }
+        if let Some(stats) = current.and_then(|key| self.get(key)) {
+            let best_time = stats.best_time.map_or_else(|| String::from("-"), format_time);
+            let best_moves = stats.best_moves.map_or_else(|| String::from("-"), |moves| moves.to_string());
+            lines.push(format!("This Level: Best Time {} / Moves {}", best_time, best_moves));
+            if let Some(last) = stats.last {
+                lines.push(format!("Last Solved {}", format_day(last.day)));
+            }
+        }
+        lines.join("\n")
+    }
+
+}
+
+pub fn format_time(secs: u64) -> String {
+    match secs >= 3600 {
+        true => format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60),
+        false => format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(time: u64, moves: u32, hints: u32) -> Solve {
+        Solve {
+            time,
+            moves,
+            hints,
+            day: 19000
+        }
+    }
+
+    #[test]
+    fn record() {
+        let mut stats = Stats::default();
+        let key = LevelKey::Pack(String::from("Tutorial"), 2);
+        stats.record(key.clone(), solve(90, 12, 1));
+        stats.record(key.clone(), solve(60, 15, 0));
+        let level = stats.get(&key).unwrap();
+        assert_eq!(level.solves, 2);
+        assert_eq!(level.total_time, 150);
+        assert_eq!(level.total_moves, 27);
+        assert_eq!(level.total_hints, 1);
+        assert_eq!(level.best_time, Some(60));
+        assert_eq!(level.best_moves, Some(12));
+        assert_eq!(level.last.map(|last| last.moves), Some(15));
+        assert!(stats.get(&LevelKey::Pack(String::from("Tutorial"), 1)).is_none());
+    }
+
+    #[test]
+    fn import_best() {
+        let mut stats = Stats::default();
+        stats.record(LevelKey::Endless(3), solve(30, 8, 0));
+        stats.import_best(&BTreeMap::from([(3, 5), (4, 9)]));
+        // an existing best isn't replaced and imported levels don't count as solved
+        assert_eq!(stats.get(&LevelKey::Endless(3)).unwrap().best_moves, Some(8));
+        assert_eq!(stats.get(&LevelKey::Endless(4)).unwrap().best_moves, Some(9));
+        assert_eq!(stats.get(&LevelKey::Endless(4)).unwrap().solves, 0);
+        let summary = stats.summary(Some(&LevelKey::Endless(4)));
+        assert!(summary.contains("Levels Solved 1 / Solves 1"), "{}", summary);
+        assert!(summary.contains("Average Time 0:30 / Moves 8"), "{}", summary);
+        assert!(summary.contains("This Level: Best Time - / Moves 9"), "{}", summary);
+    }
+
+    #[test]
+    fn summary() {
+        let mut stats = Stats::default();
+        assert!(!stats.summary(None).contains("Average"));
+        stats.record(LevelKey::Endless(7), solve(3700, 20, 2));
+        stats.record(LevelKey::Pack(String::from("Tutorial"), 0), solve(45, 4, 0));
+        let summary = stats.summary(Some(&LevelKey::Endless(7)));
+        assert!(summary.contains("Total Time 1:02:25 / Moves 24 / Hints 2"), "{}", summary);
+        assert!(summary.contains("Fastest Solve 0:45 (Tutorial 1)"), "{}", summary);
+        assert!(summary.contains("Last Solved 2022-01-08"), "{}", summary);
+    }
+
+    #[test]
+    fn time_format() {
+        assert_eq!(format_time(0), "0:00");
+        assert_eq!(format_time(605), "10:05");
+        assert_eq!(format_time(3600), "1:00:00");
+    }
+
+}
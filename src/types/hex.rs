@@ -2,8 +2,9 @@ use std::fmt::{Debug, Formatter};
 use std::iter::{once, repeat};
 use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 use glam::{Mat2, Vec2, Vec3};
+use serde::{Serialize, Deserialize};
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct HexPos(i32, i32);
 
 const NEIGHBOR_OFFSETS: &[HexPos] = &[
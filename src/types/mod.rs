@@ -1,9 +1,14 @@
 mod angle;
+#[cfg(feature = "app")]
 mod color;
 mod hex;
+mod topology;
 
 pub use angle::Angle;
+#[cfg(feature = "app")]
 pub use color::Rgba;
 pub use hex::HexPos;
+pub use topology::Topology;
 
+#[cfg(feature = "app")]
 pub type Color = Rgba<u8>;
\ No newline at end of file
@@ -0,0 +1,112 @@
+use std::str::FromStr;
+use anyhow::bail;
+use glam::Vec2;
+use serde::{Serialize, Deserialize};
+use crate::types::HexPos;
+
+const HEXAGONAL_NEIGHBOR_OFFSETS: &[HexPos] = &[
+    HexPos::new(1, 0), HexPos::new(1, -1), HexPos::new(0, -1),
+    HexPos::new(-1, 0), HexPos::new(-1, 1), HexPos::new(0, 1)
+];
+
+const SQUARE_NEIGHBOR_OFFSETS: &[HexPos] = &[
+    HexPos::new(1, 0), HexPos::new(0, -1),
+    HexPos::new(-1, 0), HexPos::new(0, 1)
+];
+
+//f32::sqrt(3.0)
+const SQUARE_SPACING: f32 = 1.7320508;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Topology {
+    #[default]
+    Hexagonal,
+    Square
+}
+
+impl Topology {
+
+    pub fn neighbor_offsets(self) -> &'static [HexPos] {
+        match self {
+            Topology::Hexagonal => HEXAGONAL_NEIGHBOR_OFFSETS,
+            Topology::Square => SQUARE_NEIGHBOR_OFFSETS
+        }
+    }
+
+    pub fn sides(self) -> usize {
+        self.neighbor_offsets().len()
+    }
+
+    pub fn opposite(self, side: usize) -> usize {
+        (side + self.sides() / 2) % self.sides()
+    }
+
+    pub fn neighbors(self, pos: HexPos) -> impl Iterator<Item=HexPos> {
+        self.neighbor_offsets().iter().map(move |offset| pos + *offset)
+    }
+
+    pub fn in_bounds(self, radius: i32, pos: HexPos) -> bool {
+        match self {
+            Topology::Hexagonal => pos.q().abs() <= radius && pos.r().abs() <= radius && pos.s().abs() <= radius,
+            Topology::Square => pos.q().abs() <= radius && pos.r().abs() <= radius
+        }
+    }
+
+    // the centers of the neighboring copies of a board with the given radius when tiling the plane
+    pub fn wrap_offsets(self, radius: i32) -> impl Iterator<Item=HexPos> {
+        let n = radius;
+        let d = 2 * n + 1;
+        let (offsets, count) = match self {
+            Topology::Hexagonal => ([
+                HexPos::new(2 * n + 1, -n), HexPos::new(n + 1, -2 * n - 1), HexPos::new(-n, -n - 1),
+                HexPos::new(-2 * n - 1, n), HexPos::new(-n - 1, 2 * n + 1), HexPos::new(n, n + 1),
+                HexPos::CENTER, HexPos::CENTER
+            ], 6),
+            Topology::Square => ([
+                HexPos::new(-d, -d), HexPos::new(-d, 0), HexPos::new(-d, d), HexPos::new(0, -d),
+                HexPos::new(0, d), HexPos::new(d, -d), HexPos::new(d, 0), HexPos::new(d, d)
+            ], 8)
+        };
+        offsets.into_iter().take(count)
+    }
+
+    pub fn wrap(self, radius: i32, pos: HexPos) -> HexPos {
+        if self.in_bounds(radius, pos) {
+            return pos;
+        }
+        self.wrap_offsets(radius)
+            .map(|offset| pos - offset)
+            .find(|pos| self.in_bounds(radius, *pos))
+            .unwrap_or(pos)
+    }
+
+    pub fn to_point(self, pos: HexPos) -> Vec2 {
+        match self {
+            Topology::Hexagonal => pos.into(),
+            Topology::Square => Vec2::new(pos.q() as f32, pos.r() as f32) * SQUARE_SPACING
+        }
+    }
+
+    pub fn from_point(self, pt: Vec2) -> HexPos {
+        match self {
+            Topology::Hexagonal => pt.into(),
+            Topology::Square => {
+                let pt = (pt / SQUARE_SPACING).round();
+                HexPos::new(pt.x as i32, pt.y as i32)
+            }
+        }
+    }
+
+}
+
+impl FromStr for Topology {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hexagonal" => Ok(Topology::Hexagonal),
+            "square" => Ok(Topology::Square),
+            _ => bail!("unknown topology \"{}\"", s)
+        }
+    }
+}
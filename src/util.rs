@@ -13,10 +13,12 @@ impl<T: Eq> Update for T {
     }
 }
 
+#[cfg(feature = "app")]
 pub trait OptionExt<T> {
     fn contains_e<U: PartialEq<T>>(&self, x: &U) -> bool;
 }
 
+#[cfg(feature = "app")]
 impl<T> OptionExt<T> for Option<T> {
     fn contains_e<U: PartialEq<T>>(&self, x: &U) -> bool {
         match self {
@@ -0,0 +1,136 @@
+use std::fmt::{Display, Formatter};
+use std::ops::RangeBounds;
+use crate::world::solver::SolverStats;
+use crate::world::tiles::TileConfig;
+use crate::world::{World, WorldParams};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum DifficultyLabel {
+    Easy,
+    Medium,
+    Hard,
+    Expert
+}
+
+impl Display for DifficultyLabel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DifficultyLabel::Easy => write!(f, "Easy"),
+            DifficultyLabel::Medium => write!(f, "Medium"),
+            DifficultyLabel::Hard => write!(f, "Hard"),
+            DifficultyLabel::Expert => write!(f, "Expert")
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Difficulty {
+    pub stats: SolverStats,
+    pub tiles: usize,
+    pub junctions: usize,
+    pub symmetric: usize
+}
+
+impl Difficulty {
+
+    pub fn analyze(world: &World) -> Option<Self> {
+        let stats = world.solver().analyze()?;
+        let mut result = Self {
+            stats,
+            tiles: 0,
+            junctions: 0,
+            symmetric: 0
+        };
+        for (pos, tile) in world.iter() {
+            if world.is_locked(pos) {
+                continue;
+            }
+            if let TileConfig::Tile(tile_type, ..) = tile {
+                result.tiles += 1;
+                if tile_type.connections() >= 3 {
+                    result.junctions += 1;
+                }
+                if (tile_type.period() as usize) < tile_type.sides() {
+                    result.symmetric += 1;
+                }
+            }
+        }
+        Some(result)
+    }
+
+    // Long deduction chains and guessing dominate, junctions add a little because they
+    // interact with more neighbors and symmetric tiles take a little away because they
+    // have fewer distinct rotations.
+    pub fn score(&self) -> f32 {
+        0.05 * self.stats.deductions as f32 +
+        1.5 * self.stats.rounds as f32 +
+        4.0 * self.stats.guesses as f32 +
+        8.0 * self.stats.max_depth as f32 +
+        0.1 * self.junctions as f32 -
+        0.05 * self.symmetric as f32
+    }
+
+    pub fn label(&self) -> DifficultyLabel {
+        match self.score() {
+            s if s < 6.0 => DifficultyLabel::Easy,
+            s if s < 10.0 => DifficultyLabel::Medium,
+            s if s < 15.0 => DifficultyLabel::Hard,
+            _ => DifficultyLabel::Expert
+        }
+    }
+
+    pub fn find_seed(seeds: impl IntoIterator<Item=u64>, params: WorldParams, band: impl RangeBounds<f32>) -> Option<u64> {
+        seeds
+            .into_iter()
+            .find(|seed| World::try_with_params(*seed, params.clone())
+                .ok()
+                .and_then(|world| world.difficulty())
+                .is_some_and(|d| band.contains(&d.score())))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(params: WorldParams) -> impl Iterator<Item=Difficulty> {
+        (0..20).filter_map(move |seed| World::with_params(seed, params.clone()).difficulty())
+    }
+
+    #[test]
+    fn labels_follow_score() {
+        let mut difficulties: Vec<Difficulty> = [2, 4, 6]
+            .into_iter()
+            .flat_map(|radius| scores(WorldParams::default().with_radius(radius)))
+            .collect();
+        let guesses = Difficulty {
+            stats: SolverStats {
+                guesses: 4,
+                max_depth: 2,
+                ..Default::default()
+            },
+            tiles: 0,
+            junctions: 0,
+            symmetric: 0
+        };
+        difficulties.push(guesses);
+        difficulties.sort_by(|a, b| a.score().total_cmp(&b.score()));
+        assert!(difficulties.windows(2).all(|w| w[0].label() <= w[1].label()));
+        assert_eq!(difficulties.first().unwrap().label(), DifficultyLabel::Easy);
+        assert_eq!(difficulties.last().unwrap().label(), DifficultyLabel::Expert);
+    }
+
+    #[test]
+    fn find_seed() {
+        let params = WorldParams::default().with_radius(4);
+        let band = 7.0..8.0;
+        let seed = Difficulty::find_seed(0..100, params.clone(), band.clone()).unwrap();
+        let score = |seed| World::with_params(seed, params.clone()).difficulty().unwrap().score();
+        assert!(band.contains(&score(seed)));
+        assert!((0..seed).all(|seed| !band.contains(&score(seed))));
+        assert_eq!(Difficulty::find_seed(0..100, params.clone(), ..0.0), None);
+        assert_eq!(Difficulty::find_seed(10..10, params, ..), None);
+    }
+
+}
@@ -0,0 +1,119 @@
+use std::fmt::{Debug, Formatter};
+
+// sized for a fixed number of elements, sets are only combined with sets of the same size
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
+pub struct BitSet(Vec<u64>);
+
+impl BitSet {
+
+    pub fn empty(size: usize) -> Self {
+        Self(vec![0; size.div_ceil(u64::BITS as usize)])
+    }
+
+    pub fn full(size: usize) -> Self {
+        Self(vec![u64::MAX; size.div_ceil(u64::BITS as usize)])
+    }
+
+    pub fn singleton(size: usize, x: usize) -> Self {
+        Self::empty(size).insert(x)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.0.len() * u64::BITS as usize
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn insert(mut self, x: usize) -> Self {
+        self.0[x / 64] |= 1 << (x % 64);
+        self
+    }
+
+    pub fn remove(mut self, x: usize) -> Self {
+        self.0[x / 64] &= !(1 << (x % 64));
+        self
+    }
+
+    pub fn union(mut self, other: &Self) -> Self {
+        debug_assert_eq!(self.0.len(), other.0.len());
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a |= b;
+        }
+        self
+    }
+
+    pub fn inter(mut self, other: &Self) -> Self {
+        debug_assert_eq!(self.0.len(), other.0.len());
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a &= b;
+        }
+        self
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || match word {
+                0 => None,
+                _ => {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(i * 64 + bit)
+                }
+            })
+        })
+    }
+
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_boundaries() {
+        let set = BitSet::empty(512)
+            .insert(0)
+            .insert(63)
+            .insert(64)
+            .insert(511);
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 63, 64, 511]);
+        assert_eq!(set.clone().remove(64).iter().collect::<Vec<_>>(), vec![0, 63, 511]);
+        assert_eq!(BitSet::full(512).len(), set.capacity());
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(BitSet::empty(1).capacity(), 64);
+        assert_eq!(BitSet::empty(64).capacity(), 64);
+        assert_eq!(BitSet::empty(65).capacity(), 128);
+        let set = BitSet::singleton(2000, 1999).insert(700);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![700, 1999]);
+        assert_eq!(set.inter(&BitSet::full(2000)).len(), 2);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = BitSet::singleton(512, 3).insert(100);
+        let b = BitSet::singleton(512, 100).insert(300);
+        assert_eq!(a.clone().union(&b).iter().collect::<Vec<_>>(), vec![3, 100, 300]);
+        assert_eq!(a.clone().inter(&b), BitSet::singleton(512, 100));
+        assert!(BitSet::singleton(512, 100).is_subset(&a));
+        assert!(!a.is_subset(&b));
+        assert_eq!(a.inter(&BitSet::empty(512)).len(), 0);
+    }
+
+}
@@ -1,27 +1,74 @@
 use std::collections::VecDeque;
+use std::sync::Mutex;
+use hashbrown::HashMap;
 use lazy_static::lazy_static;
-use crate::world::tiles::{TileConfig};
+use crate::world::tiles::{TileConfig, TileType};
 use fastrand::Rng;
 use priority_queue::PriorityQueue;
+use serde::{Serialize, Deserialize};
 use crate::HexPos;
+use crate::types::Topology;
+use crate::world::bitset::BitSet;
 use crate::world::map::HexMap;
+use crate::world::params::{TileSet, WorldParams};
 
-type IndexSet = smallbitset::Set64;
+type IndexSet = BitSet;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum GeneratorVersion {
+    // saves from before the version was stored were generated with V1
+    #[default]
+    V1,
+    // weighted u32 draws and backtracking on contradictions
+    V2
+}
+
+impl GeneratorVersion {
+    pub const LATEST: Self = Self::V2;
+
+    fn backtracks(self) -> bool {
+        self >= Self::V2
+    }
+}
+
+const MAX_DECISIONS: usize = 16;
+
+struct Decision {
+    pos: HexPos,
+    selected: usize,
+    trail_len: usize
+}
 
 pub struct PossibilityMap {
+    version: GeneratorVersion,
+    table: &'static ElementTable,
+    weights: Vec<u32>,
     map: HexMap<IndexSet>,
     propagation_queue: VecDeque<HexPos>,
     minimal_nodes: PriorityQueue<HexPos, usize>,
+    decisions: VecDeque<Decision>,
+    trail: Vec<(HexPos, IndexSet)>,
+    backtracks: usize,
+    cells: usize,
     rng: Rng
 }
 
 impl PossibilityMap {
 
-    pub fn new(radius: i32, seed: u64) -> Self {
+    pub fn new(params: &WorldParams, seed: u64, version: GeneratorVersion) -> Self {
+        let table = ElementTable::get(params.topology, params.tile_set);
+        let map = HexMap::new(params.topology, params.shape, params.radius).with_wrap(params.wrap);
         Self {
-            map: HexMap::new(radius),
+            version,
+            table,
+            weights: table.elements.iter().map(|e| params.weights.get(*e)).collect(),
+            cells: map.keys().count(),
+            map,
             propagation_queue: VecDeque::new(),
             minimal_nodes: PriorityQueue::new(),
+            decisions: VecDeque::new(),
+            trail: Vec::new(),
+            backtracks: 0,
             rng: Rng::with_seed(seed)
         }
     }
@@ -29,26 +76,37 @@ impl PossibilityMap {
     pub fn clear(&mut self) -> Result<(), ()>{
         self.propagation_queue.clear();
         self.minimal_nodes.clear();
-        self.map.fill(*COMPLETE_SET);
+        self.decisions.clear();
+        self.trail.clear();
+        self.backtracks = 0;
+        self.map.fill(self.table.complete.clone());
+        if self.map.wraps() {
+            // without a border nothing constrains the board yet
+            let complete = &self.table.complete;
+            for pos in self.map.keys() {
+                self.minimal_nodes.push(pos, complete.capacity() - complete.len());
+            }
+            return Ok(());
+        }
 
-        for pos in HexPos::ring_iter(self.map.center(), self.map.radius()) {
-            let set = pos
-                .neighbors()
+        let topology = self.map.topology();
+        let border: Vec<HexPos> = self.map.border().collect();
+        for pos in border {
+            let set = self.map
+                .neighbors(pos)
                 .enumerate()
-                .map(|(d, n)| match self.map.contains(n) {
-                    true => IndexSet::full(),
-                    false => ADJACENCY_LISTS[*EMPTY_ELEMENT_INDEX][(d + 3) % 6]
-                })
-                .fold(IndexSet::full(), |acc, x| acc.inter(x));
-            self.intersect(pos, set)?;
+                .filter(|(_, n)| !self.map.contains(*n))
+                .fold(self.table.complete.clone(), |acc, (d, _)| acc
+                    .inter(&self.table.adjacency[self.table.empty][topology.opposite(d)]));
+            self.intersect(pos, &set)?;
         }
         self.propagate()
     }
 
-    fn intersect(&mut self, pos: HexPos, value: IndexSet) -> Result<(), ()> {
+    fn intersect(&mut self, pos: HexPos, value: &IndexSet) -> Result<(), ()> {
         let field = self.map.get_mut(pos).unwrap();
-        let intersection = field.inter(value);
-        if *field != intersection {
+        if !field.is_subset(value) {
+            let intersection = field.clone().inter(value);
             match intersection.len() {
                 0 => return Err(()),
                 1 => {
@@ -58,7 +116,10 @@ impl PossibilityMap {
                     self.minimal_nodes.push(pos, intersection.capacity() - i);
                 }
             };
-            *field = intersection;
+            let previous = std::mem::replace(field, intersection);
+            if self.version.backtracks() {
+                self.trail.push((pos, previous));
+            }
             self.propagation_queue.push_back(pos);
         }
         Ok(())
@@ -69,27 +130,94 @@ impl PossibilityMap {
     }
 
     pub fn collapse(&mut self, pos: HexPos) -> Result<(), ()> {
-        let elem = self.map.get(pos).unwrap();
+        let elem = self.map.get(pos).unwrap().clone();
+        // V1 used a usize which draws from a different stream on 32-bit targets
+        let index = match self.version {
+            GeneratorVersion::V1 => self.rng.usize(0..elem.len()),
+            _ => match self.weighted_index(&elem) {
+                Ok(index) => index,
+                Err(()) => {
+                    // the tile stays undecided for whatever comes after backtracking
+                    self.minimal_nodes.push(pos, elem.capacity() - elem.len());
+                    return Err(());
+                }
+            }
+        };
         let selected = elem
             .iter()
-            .nth(self.rng.usize(0..elem.len()))
+            .nth(index)
             .unwrap();
-        self.intersect(pos, IndexSet::singleton(selected))?;
+        if self.version.backtracks() {
+            if self.decisions.len() == MAX_DECISIONS {
+                self.decisions.pop_front();
+            }
+            self.decisions.push_back(Decision {
+                pos,
+                selected,
+                trail_len: self.trail.len()
+            });
+        }
+        self.intersect(pos, &IndexSet::singleton(self.table.elements.len(), selected))?;
         self.propagate()
     }
 
+    fn weighted_index(&self, elem: &IndexSet) -> Result<usize, ()> {
+        let total: u32 = elem.iter().map(|i| self.weights[i]).sum();
+        if total == 0 {
+            return Err(());
+        }
+        let mut target = self.rng.u32(0..total);
+        Ok(elem.iter()
+            .position(|i| match target.checked_sub(self.weights[i]) {
+                Some(rest) => {
+                    target = rest;
+                    false
+                },
+                None => true
+            })
+            .unwrap())
+    }
+
+    pub fn backtrack(&mut self) -> Result<(), ()> {
+        while let Some(decision) = self.decisions.pop_back() {
+            // the storage also covers the cells outside of the shape
+            if self.backtracks >= self.cells {
+                break;
+            }
+            self.backtracks += 1;
+            while self.trail.len() > decision.trail_len {
+                let (pos, set) = self.trail.pop().unwrap();
+                match set.len() {
+                    1 => {
+                        self.minimal_nodes.remove(&pos);
+                    },
+                    i => {
+                        self.minimal_nodes.push(pos, set.capacity() - i);
+                    }
+                };
+                self.map[pos] = set;
+            }
+            self.propagation_queue.clear();
+            let remaining = IndexSet::full(self.table.elements.len()).remove(decision.selected);
+            if self.intersect(decision.pos, &remaining).and_then(|_| self.propagate()).is_ok() {
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
     pub fn propagate(&mut self) -> Result<(), ()>{
         loop {
             match self.propagation_queue.pop_front() {
                 None => break Ok(()),
                 Some(pos) => {
-                    for (index, neighbor) in pos.neighbors().enumerate() {
+                    for (index, neighbor) in self.map.neighbors(pos).enumerate() {
                         if self.map.contains(neighbor) {
                             let adl = self.map.get(pos).unwrap()
                                 .iter()
-                                .map(|x| ADJACENCY_LISTS[x as usize][index])
-                                .fold(IndexSet::empty(), |acc, x| acc.union(x));
-                            self.intersect(neighbor, adl)?;
+                                .fold(IndexSet::empty(self.table.elements.len()), |acc, x| acc
+                                    .union(&self.table.adjacency[x][index]));
+                            self.intersect(neighbor, &adl)?;
                         }
                     }
                 }
@@ -99,55 +227,145 @@ impl PossibilityMap {
 
 }
 
-impl From<PossibilityMap> for HexMap<TileConfig> {
+impl From<&PossibilityMap> for HexMap<TileConfig> {
 
-    fn from(map: PossibilityMap) -> Self {
-        assert!(!map.map.values().any(|set| set.len() != 1));
-        Self::from(&map.map, |set| ELEMENT_TABLE[set.iter().next().unwrap() as usize])
+    fn from(map: &PossibilityMap) -> Self {
+        assert!(map.map.keys().all(|pos| map.map[pos].len() == 1));
+        Self::from(&map.map, |set| set
+            .iter()
+            .next()
+            .map_or(TileConfig::Empty, |i| map.table.elements[i]))
     }
 
 }
 
-lazy_static! {
-    static ref ELEMENT_TABLE: Vec<TileConfig> = {
-        let mut result = Vec::new();
-        for tile_type in enum_iterator::all() {
-            for rotation in 0..6 {
-                result.push(TileConfig::Tile(tile_type, rotation));
+struct ElementTable {
+    elements: Vec<TileConfig>,
+    empty: usize,
+    complete: IndexSet,
+    adjacency: Vec<[IndexSet; 6]>
+}
+
+impl ElementTable {
+
+    // colors are painted onto the finished layout, so the table only holds uncolored tiles
+    fn new(topology: Topology, tile_set: TileSet) -> Self {
+        let mut elements = Vec::new();
+        let tile_types = enum_iterator::all::<TileType>()
+            .filter(|t| t.topology() == topology)
+            .filter(|t| tile_set == TileSet::Complete || t.tile_set() == TileSet::Standard);
+        for tile_type in tile_types {
+            for rotation in 0..topology.sides() {
+                elements.push(TileConfig::Tile(tile_type, rotation as u8, [0; 6]));
             }
         }
-        result.push(TileConfig::Empty);
-        result
-    };
-    static ref EMPTY_ELEMENT_INDEX: usize = {
-        ELEMENT_TABLE
+        elements.push(TileConfig::Empty);
+        let size = elements.len();
+
+        let empty = elements
             .iter()
             .position(|x| *x == TileConfig::Empty)
-            .expect("Cannot find the empty element in table")
-    };
-    static ref COMPLETE_SET: IndexSet = {
-        (0..ELEMENT_TABLE.len())
-            .into_iter()
-            .map(|x| IndexSet::singleton(x as u8))
-            .fold(IndexSet::empty(), |acc, x| acc.union(x))
-    };
-    static ref ADJACENCY_LISTS: Vec<[IndexSet; 6]> = {
-        assert!(ELEMENT_TABLE.len() <= IndexSet::full().len());
-        let mut result = Vec::new();
-        for i in 0..ELEMENT_TABLE.len() {
-            let mut lists = [IndexSet::empty(); 6];
-            for j in 0..lists.len() {
-                for k in 0..ELEMENT_TABLE.len() {
-                    let elem1 = ELEMENT_TABLE[i].endings();
-                    let elem2 = ELEMENT_TABLE[k].endings();
-
-                    if elem1[j] == elem2[(j + 3) % elem2.len()] {
-                        lists[j] = lists[j].insert(k as u8);
-                    }
-                }
+            .expect("Cannot find the empty element in table");
+        let complete = (0..size).fold(IndexSet::empty(size), |acc, x| acc.insert(x));
+
+        let mut adjacency = Vec::new();
+        for elem1 in &elements {
+            adjacency.push(std::array::from_fn(|j| elements
+                .iter()
+                .enumerate()
+                .filter(|(_, elem2)| j < topology.sides() && elem1.edge(j) == elem2.edge(topology.opposite(j)))
+                .fold(IndexSet::empty(size), |acc, (k, _)| acc.insert(k))));
+        }
+
+        Self {
+            elements,
+            empty,
+            complete,
+            adjacency
+        }
+    }
+
+    fn get(topology: Topology, tile_set: TileSet) -> &'static Self {
+        // the square grid only has a single tile set
+        let tile_set = match topology {
+            Topology::Hexagonal => tile_set,
+            Topology::Square => TileSet::Complete
+        };
+        let mut tables = ELEMENT_TABLES.lock().unwrap();
+        if let Some(table) = tables.get(&(topology, tile_set)) {
+            return table;
+        }
+        let table = Box::leak(Box::new(Self::new(topology, tile_set)));
+        tables.insert((topology, tile_set), table);
+        table
+    }
+
+}
+
+lazy_static! {
+    static ref ELEMENT_TABLES: Mutex<HashMap<(Topology, TileSet), &'static ElementTable>> = Mutex::new(HashMap::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use hashbrown::HashSet;
+    use super::*;
+    use crate::world::params::TileWeights;
+    use crate::world::World;
+
+    // fails instead of restarting when backtracking can't resolve a contradiction
+    fn generate_without_restart(wfc: &mut PossibilityMap) -> Option<(HexMap<TileConfig>, usize)> {
+        wfc.clear().ok()?;
+        let mut contradictions = 0;
+        while let Some(pos) = wfc.lowest_entropy() {
+            if wfc.collapse(pos).is_err() {
+                contradictions += 1;
+                wfc.backtrack().ok()?;
             }
-            result.push(lists);
         }
-        result
-    };
+        Some(((&*wfc).into(), contradictions))
+    }
+
+    #[test]
+    fn zero_weights() {
+        let params = WorldParams::default()
+            .with_weights(TileWeights::busy());
+        let table = ElementTable::get(params.topology, params.tile_set);
+        let empty = IndexSet::singleton(table.elements.len(), table.empty);
+        assert_eq!(PossibilityMap::new(&params, 0, GeneratorVersion::V2).weighted_index(&empty), Err(()));
+    }
+
+    #[test]
+    fn large_tables() {
+        // the complete tile set doesn't fit into a single word of the bitset
+        let table = ElementTable::get(Topology::Hexagonal, TileSet::Complete);
+        assert!(table.elements.len() > 64);
+        assert_eq!(table.complete.len(), table.elements.len());
+        let params = WorldParams::default().with_tile_set(TileSet::Complete).with_radius(3);
+        let mut wfc = PossibilityMap::new(&params, 0, GeneratorVersion::V2);
+        let tiles = World::generate(&mut wfc).unwrap();
+        assert!(tiles.values().any(|tile| matches!(tile, TileConfig::Tile(t, ..) if t.tile_set() == TileSet::Complete)));
+        assert!(World::from_tiles(0, params, tiles, HashSet::new()).is_completed());
+    }
+
+    #[test]
+    fn backtracking() {
+        // without empty tiles the draws run into cells that only allow tiles without weight
+        let params = WorldParams::default()
+            .with_radius(6)
+            .with_weights(TileWeights::busy());
+        let mut recovered = 0;
+        for seed in 0..20 {
+            let mut wfc = PossibilityMap::new(&params, seed, GeneratorVersion::V2);
+            let (tiles, contradictions) = generate_without_restart(&mut wfc)
+                .unwrap_or_else(|| panic!("seed {} needed a restart", seed));
+            if contradictions > 0 {
+                assert!(wfc.backtracks > 0);
+                recovered += 1;
+            }
+            assert!(World::from_tiles(seed, params.clone(), tiles, HashSet::new()).is_completed(), "seed {}", seed);
+        }
+        assert!(recovered > 0);
+    }
+
 }
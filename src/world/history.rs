@@ -0,0 +1,75 @@
+use serde::{Serialize, Deserialize};
+use crate::HexPos;
+use crate::world::Direction;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct History {
+    undo: Vec<(HexPos, Direction)>,
+    redo: Vec<(HexPos, Direction)>
+}
+
+impl History {
+
+    pub fn push(&mut self, pos: HexPos, direction: Direction) {
+        self.undo.push((pos, direction));
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self) -> Option<(HexPos, Direction)> {
+        let (pos, direction) = self.undo.pop()?;
+        self.redo.push((pos, direction));
+        Some((pos, direction.inverse()))
+    }
+
+    pub fn redo(&mut self) -> Option<(HexPos, Direction)> {
+        let (pos, direction) = self.redo.pop()?;
+        self.undo.push((pos, direction));
+        Some((pos, direction))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: HexPos = HexPos::new(0, 0);
+    const B: HexPos = HexPos::new(1, 0);
+
+    #[test]
+    fn undo_redo_order() {
+        let mut history = History::default();
+        history.push(A, Direction::CW);
+        history.push(B, Direction::CCW);
+        assert_eq!(history.undo(), Some((B, Direction::CW)));
+        assert_eq!(history.undo(), Some((A, Direction::CCW)));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some((A, Direction::CW)));
+        assert_eq!(history.redo(), Some((B, Direction::CCW)));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some((B, Direction::CW)));
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let mut history = History::default();
+        history.push(A, Direction::CW);
+        history.push(B, Direction::CW);
+        history.undo();
+        history.push(A, Direction::CCW);
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some((A, Direction::CW)));
+        assert_eq!(history.undo(), Some((A, Direction::CCW)));
+        assert_eq!(history.undo(), None);
+    }
+
+    #[test]
+    fn empty_history() {
+        let mut history = History::default();
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), None);
+        history.push(A, Direction::CW);
+        assert_eq!(history.undo(), Some((A, Direction::CCW)));
+    }
+
+}
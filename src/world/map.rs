@@ -1,10 +1,15 @@
 use std::fmt::{Debug, Formatter};
 use std::ops::{Index, IndexMut};
 use crate::HexPos;
+use crate::types::Topology;
+use crate::world::params::BoardShape;
 
 #[derive(Clone)]
 pub struct HexMap<T> {
+    topology: Topology,
+    shape: BoardShape,
     radius: i32,
+    wrap: bool,
     elements: Box<[T]>
 }
 
@@ -32,19 +37,32 @@ impl<T> IndexMut<HexPos> for HexMap<T> {
 
 
 impl<T: Default + Clone> HexMap<T> {
-    pub fn new(radius: i32) -> Self {
+    pub fn new(topology: Topology, shape: BoardShape, radius: i32) -> Self {
         debug_assert!(radius >= 0);
         let diameter = 2 * radius + 1;
-        let len = diameter * diameter - radius * radius - radius;
+        let len = match topology {
+            Topology::Hexagonal => diameter * diameter - radius * radius - radius,
+            Topology::Square => diameter * diameter
+        };
         let elements = vec![Default::default(); len as usize].into_boxed_slice();
         Self {
+            topology,
+            shape,
             radius,
+            wrap: false,
             elements
         }
     }
 
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
     pub fn fill(&mut self, value: T) {
-        self.elements.fill(value)
+        for pos in self.keys() {
+            self[pos] = value.clone();
+        }
     }
 }
 
@@ -52,7 +70,10 @@ impl<T> HexMap<T> {
 
     pub fn from<U>(old: &HexMap<U>, func: impl Fn(&U) -> T) -> Self {
         Self {
+            topology: old.topology,
+            shape: old.shape,
             radius: old.radius,
+            wrap: old.wrap,
             elements: old.elements.iter().map(func).collect()
         }
     }
@@ -61,8 +82,52 @@ impl<T> HexMap<T> {
         self.elements.len()
     }
 
-    pub fn keys(&self) -> impl Iterator<Item=HexPos> {
-        HexPos::spiral_iter(self.center(), self.radius)
+    pub fn keys(&self) -> Box<dyn Iterator<Item=HexPos>> {
+        let (topology, shape, radius) = (self.topology, self.shape, self.radius);
+        let keys: Box<dyn Iterator<Item=HexPos>> = match topology {
+            Topology::Hexagonal => Box::new(HexPos::spiral_iter(self.center(), radius)),
+            Topology::Square => Box::new((-radius..=radius)
+                .flat_map(move |q| (-radius..=radius).map(move |r| HexPos::new(q, r))))
+        };
+        match shape {
+            BoardShape::Full => keys,
+            _ => Box::new(keys.filter(move |pos| shape.contains(topology, radius, *pos)))
+        }
+    }
+
+    pub fn border(&self) -> Box<dyn Iterator<Item=HexPos> + '_> {
+        match (self.topology, self.shape) {
+            (Topology::Hexagonal, BoardShape::Full) if self.radius > 0 => Box::new(HexPos::ring_iter(self.center(), self.radius)),
+            _ => Box::new(self
+                .keys()
+                .filter(|pos| self.neighbors(*pos).any(|n| !self.contains(n))))
+        }
+    }
+
+    pub fn neighbors(&self, pos: HexPos) -> impl Iterator<Item=HexPos> {
+        let (topology, radius, wraps) = (self.topology, self.radius, self.wraps());
+        topology
+            .neighbors(pos)
+            .map(move |n| match wraps {
+                true => topology.wrap(radius, n),
+                false => n
+            })
+    }
+
+    // wrapping only makes sense for boards that can tile the plane
+    pub fn wraps(&self) -> bool {
+        self.wrap && self.shape == BoardShape::Full && self.radius > 0
+    }
+
+    pub fn wrap(&self, pos: HexPos) -> HexPos {
+        match self.wraps() {
+            true => self.topology.wrap(self.radius, pos),
+            false => pos
+        }
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
     }
 
     pub fn values(&self) -> impl Iterator<Item=&T> {
@@ -84,6 +149,9 @@ impl<T> HexMap<T> {
     pub fn index(&self, pos: HexPos) -> Option<usize> {
         if !self.contains(pos) {
             None
+        } else if self.topology == Topology::Square {
+            let diameter = 2 * self.radius + 1;
+            Some(((pos.q() + self.radius) * diameter + pos.r() + self.radius) as usize)
         } else {
             let diameter = 2 * self.radius + 1;
             let len = self.elements.len() as i32;
@@ -96,7 +164,7 @@ impl<T> HexMap<T> {
     }
 
     pub fn contains(&self, pos: HexPos) -> bool {
-        pos.q().abs() <= self.radius && pos.r().abs() <= self.radius && pos.s().abs() <= self.radius
+        self.topology.in_bounds(self.radius, pos) && self.shape.contains(self.topology, self.radius, pos)
     }
 
     pub fn get(&self, pos: HexPos) -> Option<&T> {
@@ -108,3 +176,30 @@ impl<T> HexMap<T> {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapped_neighbors() {
+        let square: HexMap<u8> = HexMap::new(Topology::Square, BoardShape::Full, 2).with_wrap(true);
+        assert!(square.neighbors(HexPos::new(2, 0)).any(|n| n == HexPos::new(-2, 0)));
+        assert!(square.neighbors(HexPos::new(1, -2)).any(|n| n == HexPos::new(1, 2)));
+        let hexagonal: HexMap<u8> = HexMap::new(Topology::Hexagonal, BoardShape::Full, 2).with_wrap(true);
+        assert!(hexagonal.neighbors(HexPos::new(2, 0)).any(|n| n == HexPos::new(-2, 2)));
+        for map in [square, hexagonal] {
+            let topology = map.topology();
+            for pos in map.keys() {
+                for (side, neighbor) in map.neighbors(pos).enumerate() {
+                    assert!(map.contains(neighbor), "{:?} of {:?}", neighbor, pos);
+                    // going back across the same edge leads to the original cell
+                    assert_eq!(map.neighbors(neighbor).nth(topology.opposite(side)), Some(pos));
+                }
+            }
+        }
+        let unwrapped: HexMap<u8> = HexMap::new(Topology::Square, BoardShape::Full, 2);
+        assert!(unwrapped.neighbors(HexPos::new(2, 0)).any(|n| !unwrapped.contains(n)));
+    }
+
+}
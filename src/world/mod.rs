@@ -1,26 +1,58 @@
 mod map;
+mod bitset;
 mod tiles;
 mod generator;
+mod params;
+mod solver;
+mod analysis;
+mod text;
+mod pack;
+mod network;
+#[cfg(feature = "app")]
+mod history;
 
+use std::iter::once;
+use anyhow::{bail, ensure};
 use fastrand::Rng;
+use instant::Instant;
 use hashbrown::HashSet;
 use serde::{Serialize, Deserialize};
 use generator::PossibilityMap;
+pub use generator::GeneratorVersion;
+use solver::Solver;
+pub use solver::SolverStats;
+pub use analysis::{Difficulty, DifficultyLabel};
+#[cfg(feature = "app")]
+pub use history::History;
 use crate::HexPos;
+use crate::types::Topology;
 use crate::util::Update;
 
 pub use tiles::*;
 pub use map::HexMap;
+pub use params::{BoardShape, GenerationMode, ScrambleParams, TileSet, TileWeights, WorldParams, MAX_COLORS};
+pub use pack::{LevelPack, PackLevel};
+pub use network::Network;
+
+const MAX_SCRAMBLE_ATTEMPTS: usize = 100;
+// boards that are generated for a unique solution before locking tiles instead
+const MAX_UNIQUE_ATTEMPTS: usize = 100;
+// restarts of the generator after contradictions it couldn't backtrack out of
+const MAX_RESTARTS: usize = 100;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(into = "WorldSave")]
-#[serde(from = "WorldSave")]
+#[serde(try_from = "WorldSave")]
 pub struct World {
     seed: u64,
+    params: WorldParams,
+    generator: GeneratorVersion,
     elements: HexMap<TileConfig>,
+    locked: HashSet<HexPos>,
     incomplete: HashSet<HexPos>
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     CW,
     CCW
@@ -33,95 +65,250 @@ impl Direction {
             false => Self::CCW
         }
     }
-}
 
-impl From<Direction> for u8 {
-    fn from(value: Direction) -> Self {
-        match value {
-            Direction::CW => 1,
-            Direction::CCW => 5
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::CW => Self::CCW,
+            Self::CCW => Self::CW
         }
     }
 }
 
 impl World {
 
-    pub fn new(seed: u64) -> Self {
+    pub fn with_params(seed: u64, params: WorldParams) -> Self {
+        Self::with_generator(seed, params, GeneratorVersion::LATEST)
+    }
+
+    pub fn with_generator(seed: u64, params: WorldParams, generator: GeneratorVersion) -> Self {
+        Self::try_with_generator(seed, params, generator).expect("Failed to generate the world")
+    }
+
+    pub fn try_with_params(seed: u64, params: WorldParams) -> anyhow::Result<Self> {
+        Self::try_with_generator(seed, params, GeneratorVersion::LATEST)
+    }
+
+    pub fn try_with_generator(seed: u64, params: WorldParams, generator: GeneratorVersion) -> anyhow::Result<Self> {
+        let now = Instant::now();
+        params.validate()?;
+
+        let mut wfc = PossibilityMap::new(&params, seed, generator);
+        let rng = Rng::with_seed(seed);
 
-        //let now = Instant::now();
+        let mut attempts = 0;
+        let (elements, locked) = loop {
+            attempts += 1;
+            let mut elements = Self::generate(&mut wfc)?;
+            Self::paint(&mut elements, params.colors, &rng);
+            let locked = Self::choose_locked(&elements, params.locked_tiles, &rng, generator);
+            match params.mode {
+                GenerationMode::Standard => break (elements, locked),
+                GenerationMode::UniqueSolution => match Solver::new(&elements).lock(&locked).solutions(2).len() {
+                    1 => break (elements, locked),
+                    _ if attempts >= MAX_UNIQUE_ATTEMPTS => {
+                        log::debug!("No unique board after {} attempts, locking tiles instead (seed: {})", attempts, seed);
+                        let locked = Self::lock_unique(&elements, locked);
+                        break (elements, locked)
+                    },
+                    _ => log::trace!("Rejected a board with multiple solutions (seed: {})", seed)
+                }
+            }
+        };
 
-        let mut wfc = PossibilityMap::new(2, seed);
+        log::debug!("Generated world {} in {}ms", seed, now.elapsed().as_millis());
 
-        'outer: loop {
-            //println!("Attempt {}", i + 1);
-            assert!(wfc.clear().is_ok());
+        Ok(Self {
+            seed,
+            params,
+            generator,
+            elements,
+            locked,
+            incomplete: HashSet::new()
+        })
+    }
 
+    pub fn from_tiles(seed: u64, params: WorldParams, tiles: HexMap<TileConfig>, locked: HashSet<HexPos>) -> Self {
+        let mut world = Self {
+            seed,
+            params,
+            generator: GeneratorVersion::LATEST,
+            elements: tiles,
+            locked,
+            incomplete: HashSet::new()
+        };
+        world.update_incomplete();
+        world
+    }
+
+    // locks tiles where two solutions differ until the generated layout is the only solution left
+    fn lock_unique(elements: &HexMap<TileConfig>, mut locked: HashSet<HexPos>) -> HashSet<HexPos> {
+        loop {
+            let solutions = Solver::new(elements).lock(&locked).solutions(2);
+            let [first, second] = solutions.as_slice() else {
+                return locked;
+            };
+            let pos = elements
+                .keys()
+                .find(|pos| first[*pos].edges() != second[*pos].edges())
+                .expect("different solutions must differ in at least one tile");
+            locked.insert(pos);
+        }
+    }
+
+    // gives both endings of a connection the same color, tiles continue the color of the first painted
+    // ending they find, so colors only change where two differently painted parts of a network meet
+    fn paint(elements: &mut HexMap<TileConfig>, colors: u8, rng: &Rng) {
+        if colors <= 1 {
+            return;
+        }
+        let topology = elements.topology();
+        let mut painted = HashSet::new();
+        for pos in elements.keys().collect::<Vec<_>>() {
+            if elements[pos] == TileConfig::Empty {
+                continue;
+            }
+            let neighbors: Vec<HexPos> = elements.neighbors(pos).collect();
+            let color = (0..topology.sides())
+                .find(|side| painted.contains(&(pos, *side)))
+                .map_or_else(|| rng.u8(..colors), |side| elements[pos].colors()[side]);
+            for (side, npos) in neighbors.into_iter().enumerate() {
+                if elements[pos].edge(side).is_none() || !painted.insert((pos, side)) {
+                    continue;
+                }
+                let mut tile_colors = elements[pos].colors();
+                tile_colors[side] = color;
+                elements[pos] = elements[pos].with_colors(tile_colors);
+                let opposite = topology.opposite(side);
+                if let Some(neighbor) = elements.get(npos).copied().filter(|n| n.edge(opposite).is_some()) {
+                    let mut neighbor_colors = neighbor.colors();
+                    neighbor_colors[opposite] = color;
+                    elements[npos] = neighbor.with_colors(neighbor_colors);
+                    painted.insert((npos, opposite));
+                }
+            }
+        }
+    }
+
+    fn choose_locked(elements: &HexMap<TileConfig>, count: usize, rng: &Rng, generator: GeneratorVersion) -> HashSet<HexPos> {
+        if count == 0 {
+            return HashSet::new();
+        }
+        let mut candidates: Vec<HexPos> = elements
+            .keys()
+            .filter(|pos| elements[*pos] != TileConfig::Empty)
+            .collect();
+        match generator {
+            // shuffle draws usize values which differ on 32-bit targets
+            GeneratorVersion::V1 => rng.shuffle(&mut candidates),
+            _ => for i in (1..candidates.len()).rev() {
+                candidates.swap(i, rng.u32(..=i as u32) as usize);
+            }
+        }
+        candidates.truncate(count.min(candidates.len() / 2));
+        candidates.into_iter().collect()
+    }
+
+    fn generate(wfc: &mut PossibilityMap) -> anyhow::Result<HexMap<TileConfig>> {
+        for _ in 0..MAX_RESTARTS {
+            ensure!(wfc.clear().is_ok(), "the border of the board can't be filled with the available tiles");
             loop {
                 match wfc.lowest_entropy() {
-                    None => break 'outer,
+                    None => return Ok((&*wfc).into()),
                     Some(index) => {
-                        if wfc.collapse(index).is_err() {
-                            continue 'outer;
+                        if wfc.collapse(index).is_err() && wfc.backtrack().is_err() {
+                            break;
                         }
                     }
                 }
             }
+            log::trace!("Restarting the generation after a contradiction");
         }
+        bail!("no board found after {} restarts", MAX_RESTARTS)
+    }
 
-        let elements = wfc.into();
-
-        //println!("Time: {}ms", now.elapsed().as_millis());
-
-        Self {
-            seed,
-            elements,
-            incomplete: HashSet::new()
-        }
+    pub fn topology(&self) -> Topology {
+        self.params.topology
     }
 
     pub fn tiles(&self) -> &HexMap<TileConfig> {
         &self.elements
     }
 
-    pub fn scramble(&mut self, force_rotation: bool) {
+    pub fn scramble(&mut self, params: ScrambleParams) -> anyhow::Result<()> {
         let rng = Rng::with_seed(self.seed());
-        while {
-            for tile in self.elements.values_mut() {
-                *tile = if force_rotation {
-                    tile.rotate_by(rng.u8(1..6))
-                } else {
-                    tile.with_rotation(rng.u8(..6))
+        let sides = self.elements.topology().sides() as u8;
+        let original = self.elements.clone();
+        // empty, fully symmetric and locked tiles look the same in every rotation
+        let rotatable: Vec<(HexPos, Vec<u8>)> = original
+            .keys()
+            .filter(|pos| !self.locked.contains(pos))
+            .map(|pos| (pos, (1..sides)
+                .filter(|r| original[pos].rotate_by(*r).edges() != original[pos].edges())
+                .collect::<Vec<u8>>()))
+            .filter(|(_, rotations)| !rotations.is_empty())
+            .collect();
+        ensure!(!rotatable.is_empty(), "the board has no tiles that can be rotated");
+        let min_misplaced = params.min_misplaced
+            .max((rotatable.len() * params.min_misplaced_percent as usize).div_ceil(100))
+            .min(rotatable.len());
+
+        for _ in 0..MAX_SCRAMBLE_ATTEMPTS {
+            for (pos, rotations) in &rotatable {
+                self.elements[*pos] = match params.force_rotation {
+                    // a usize would draw differently on 32-bit targets
+                    true => original[*pos].rotate_by(rotations[rng.u8(..rotations.len() as u8) as usize]),
+                    false => original[*pos].with_rotation(rng.u8(..sides))
                 };
             }
-            self.incomplete.clear();
-            for pos in self.elements.keys() {
-                if !self.is_tile_complete(pos) {
-                    self.incomplete.insert(pos);
-                }
+            self.update_incomplete();
+            let misplaced = rotatable
+                .iter()
+                .filter(|(pos, _)| self.elements[*pos].edges() != original[*pos].edges())
+                .count();
+            if !self.is_completed() && misplaced >= min_misplaced
+                && (params.min_par == 0 || self.par().is_some_and(|par| par >= params.min_par)) {
+                return Ok(());
+            }
+        }
+        bail!("failed to scramble the board with {} misplaced tiles and a par of {} after {} attempts",
+            min_misplaced, params.min_par, MAX_SCRAMBLE_ATTEMPTS)
+    }
+
+    fn update_incomplete(&mut self) {
+        self.incomplete.clear();
+        for pos in self.elements.keys() {
+            if !self.is_tile_complete(pos) {
+                self.incomplete.insert(pos);
             }
-            self.incomplete.is_empty()
-        } {}
+        }
     }
 
     fn is_tile_complete(&self, pos: HexPos) -> bool {
         match self.elements.get(pos) {
             None => true,
-            Some(tile) => !pos
-                .neighbors()
+            Some(tile) => !self.elements
+                .neighbors(pos)
                 .map(|npos| self.elements.get(npos).unwrap_or(&TileConfig::Empty))
                 .enumerate()
-                .any(|(i, n)| tile.endings()[i] != n.endings()[(i + 3) % 6])
+                .any(|(i, n)| tile.edge(i) != n.edge(self.elements.topology().opposite(i)))
         }
     }
 
+    pub fn is_locked(&self, pos: HexPos) -> bool {
+        self.locked.contains(&pos)
+    }
+
     pub fn try_rotate(&mut self, pos: HexPos, direction: Direction) -> bool {
+        if self.is_locked(pos) {
+            return false;
+        }
         let updated = self.elements
             .get_mut(pos)
-            .map(|t|t.update(t.rotate_by(direction.into())))
+            .map(|t|t.update(t.rotate(direction)))
             .unwrap_or(false);
 
         if updated {
-            for pos in HexPos::spiral_iter(pos, 1) {
+            for pos in once(pos).chain(self.elements.neighbors(pos)) {
                 match self.is_tile_complete(pos) {
                     true => self.incomplete.remove(&pos),
                     false => self.incomplete.insert(pos)
@@ -131,6 +318,47 @@ impl World {
         updated
     }
 
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        Difficulty::analyze(self)
+    }
+
+    fn solver(&self) -> Solver {
+        Solver::new(&self.elements).lock(&self.locked)
+    }
+
+    pub fn solve(&self) -> Option<HexMap<TileConfig>> {
+        self.solver().solve()
+    }
+
+    pub fn solutions(&self, limit: usize) -> Vec<World> {
+        self.solver()
+            .solutions(limit)
+            .into_iter()
+            .map(|elements| Self {
+                elements,
+                incomplete: HashSet::new(),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    pub fn hint(&self) -> Option<(HexPos, TileConfig)> {
+        let solution = self.solve()?;
+        let misplaced = |pos: &HexPos| self.elements[*pos].edges() != solution[*pos].edges();
+        self.elements
+            .keys()
+            .filter(misplaced)
+            .find(|pos| self.incomplete.contains(pos))
+            .or_else(|| self.elements.keys().find(misplaced))
+            .map(|pos| (pos, solution[pos]))
+    }
+
+
+    // the minimum over all solutions as the player can finish the board with any of them
+    pub fn par(&self) -> Option<u32> {
+        self.solver().min_rotations()
+    }
+
     pub fn is_completed(&self) -> bool {
         self.incomplete.is_empty()
     }
@@ -139,46 +367,397 @@ impl World {
         self.seed
     }
 
+    pub fn params(&self) -> &WorldParams {
+        &self.params
+    }
+
     pub fn iter(&self) -> impl Iterator<Item=(HexPos, TileConfig)> + '_ {
         self.elements.keys().map(move |k|(k, self.elements[k]))
     }
 
 }
 
+// 1: the board is regenerated from the seed and only the rotations are stored
+// 2: every tile is stored explicitly
+const SAVE_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct WorldSave {
+    // saves from before the version was stored don't have this field
+    #[serde(default = "WorldSave::legacy_version")]
+    version: u32,
     seed: u64,
-    rotations: Vec<u8>
+    #[serde(default)]
+    params: WorldParams,
+    #[serde(default)]
+    generator: GeneratorVersion,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rotations: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tiles: Vec<TileConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    locked: Vec<HexPos>
+}
+
+impl WorldSave {
+
+    fn legacy_version() -> u32 {
+        1
+    }
+
+    fn migrate(mut self) -> anyhow::Result<Self> {
+        if self.version < 2 {
+            let world = World::try_with_generator(self.seed, self.params.clone(), self.generator)?;
+            self.tiles = world.elements.values().copied().collect();
+            if self.rotations.len() == self.tiles.len() {
+                for (tc, r) in self.tiles.iter_mut().zip(self.rotations.iter()) {
+                    *tc = tc.with_rotation(*r);
+                }
+            } else {
+                log::warn!("Number of rotations in save doesn't match the number of tiles in this level");
+            }
+            self.rotations.clear();
+            self.locked = world.locked.into_iter().collect();
+            self.version = 2;
+        }
+        Ok(self)
+    }
+
 }
 
 impl From<World> for WorldSave {
     fn from(world: World) -> Self {
         Self {
+            version: SAVE_VERSION,
             seed: world.seed,
-            rotations: world.elements.values().map(|v| match *v {
-                TileConfig::Empty => 0,
-                TileConfig::Tile(_, r) => r
-            }).collect()
+            params: world.params,
+            generator: world.generator,
+            rotations: Vec::new(),
+            tiles: world.elements.values().copied().collect(),
+            locked: world.locked.into_iter().collect()
+        }
+    }
+}
+
+impl TryFrom<WorldSave> for World {
+    type Error = anyhow::Error;
+
+    fn try_from(save: WorldSave) -> anyhow::Result<Self> {
+        if save.version > SAVE_VERSION {
+            log::warn!("Save version {} is newer than the supported version {}", save.version, SAVE_VERSION);
+        }
+        save.params.validate()?;
+        let save = save.migrate()?;
+        let mut tiles = HexMap::new(save.params.topology, save.params.shape, save.params.radius)
+            .with_wrap(save.params.wrap);
+        if save.tiles.len() != tiles.len() {
+            log::warn!("Number of tiles in save doesn't match the size of the board");
+            return World::try_with_generator(save.seed, save.params, save.generator);
         }
+        // the colors index the palette of the shaders
+        if let Some(tile) = save.tiles.iter().find(|tile| tile.colors().iter().any(|color| *color >= MAX_COLORS)) {
+            bail!("the color of {:?} is out of range", tile);
+        }
+        for (tc, saved) in tiles.values_mut().zip(save.tiles) {
+            *tc = saved;
+        }
+        let locked = save.locked
+            .into_iter()
+            .filter(|pos| tiles.contains(*pos))
+            .collect();
+        let mut world = World::from_tiles(save.seed, save.params, tiles, locked);
+        world.generator = save.generator;
+        Ok(world)
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use params::MAX_RADIUS;
+
+    fn hexagonal() -> WorldParams {
+        WorldParams::default()
+            .with_radius(3)
+            .with_locked_tiles(3)
+    }
+
+    fn square() -> WorldParams {
+        WorldParams::default()
+            .with_topology(Topology::Square)
+            .with_radius(2)
+            .with_mode(GenerationMode::UniqueSolution)
+    }
+
+    // the layouts of existing seeds must never change for a released generator version
+    #[test]
+    fn golden_layouts() {
+        let golden = [
+            (GeneratorVersion::V1, include_str!("fixtures/golden/v1-hexagonal.txt"), include_str!("fixtures/golden/v1-square.txt")),
+            (GeneratorVersion::V2, include_str!("fixtures/golden/v2-hexagonal.txt"), include_str!("fixtures/golden/v2-square.txt"))
+        ];
+        for (generator, hex, sq) in golden {
+            assert_eq!(World::with_generator(11, hexagonal(), generator).to_string(), hex, "{:?} hexagonal", generator);
+            assert_eq!(World::with_generator(5, square(), generator).to_string(), sq, "{:?} square", generator);
+        }
+    }
+
+    // layouts produced by the original generator before it took any parameters
+    #[test]
+    fn original_layouts() {
+        let layouts: Vec<String> = (0..4)
+            .map(|seed| World::with_generator(seed, WorldParams::default(), GeneratorVersion::V1).to_string())
+            .collect();
+        assert_eq!(layouts.join("\n"), include_str!("fixtures/golden/v1-default.txt"));
+    }
+
+    #[test]
+    fn unique_solution() {
+        for seed in 0..20 {
+            let world = World::with_params(seed, square());
+            assert_eq!(world.solutions(2).len(), 1, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn lock_unique() {
+        let world = (0..)
+            .map(|seed| World::with_params(seed, WorldParams::default().with_radius(3)))
+            .find(|world| world.solutions(2).len() > 1)
+            .unwrap();
+        let locked = World::lock_unique(&world.elements, world.locked.clone());
+        assert!(locked.is_superset(&world.locked));
+        let solutions = Solver::new(&world.elements).lock(&locked).solutions(2);
+        assert_eq!(solutions.len(), 1);
+        assert!(world.elements.keys().all(|pos| solutions[0][pos].edges() == world.elements[pos].edges()));
+    }
+
+    #[test]
+    fn scramble() {
+        let params = WorldParams::default()
+            .with_radius(4)
+            .with_shape(BoardShape::Star)
+            .with_locked_tiles(3);
+        let scramble = ScrambleParams::default()
+            .with_force_rotation(true)
+            .with_min_misplaced_percent(50);
+        for seed in 0..10 {
+            let original = World::with_params(seed, params.clone());
+            let mut world = original.clone();
+            world.scramble(scramble).unwrap();
+            assert!(!world.is_completed());
+            let rotatable = original.elements
+                .keys()
+                .filter(|pos| !original.locked.contains(pos))
+                .filter(|pos| (1..6).any(|r| original.elements[*pos].rotate_by(r).edges() != original.elements[*pos].edges()))
+                .count();
+            let misplaced = original.elements
+                .keys()
+                .filter(|pos| world.elements[*pos].edges() != original.elements[*pos].edges())
+                .count();
+            assert!(misplaced * 2 >= rotatable, "seed {}: {} of {} tiles misplaced", seed, misplaced, rotatable);
+            assert!(original.locked.iter().all(|pos| world.elements[*pos] == original.elements[*pos]));
+        }
+        let mut empty = World::from_tiles(0, params.clone(), HexMap::new(params.topology, params.shape, params.radius), HashSet::new());
+        assert!(empty.scramble(scramble).is_err());
+    }
+
+    #[test]
+    fn impossible_weights() {
+        let mut weights = TileWeights::default().with_empty(0);
+        for tile_type in enum_iterator::all::<TileType>() {
+            weights = weights.with_tile(tile_type, 0);
+        }
+        assert!(World::try_with_params(0, WorldParams::default().with_weights(weights)).is_err());
+    }
+
+    #[test]
+    fn invalid_radius() {
+        assert!(World::try_with_params(0, WorldParams::default().with_radius(-1)).is_err());
+        assert!(World::try_with_params(0, WorldParams::default().with_radius(MAX_RADIUS + 1)).is_err());
+        assert!(World::try_with_params(0, WorldParams::default().with_radius(0)).is_ok());
+    }
+
+    // saves from before the format was versioned only stored the seed and the rotation of every tile
+    #[test]
+    fn migrate_rotation_save() {
+        let rotations: Vec<u8> = (0..19).map(|i| i % 6).collect();
+        let save = format!("{{\"seed\": 3, \"rotations\": {:?}}}", rotations);
+        let world: World = serde_json::from_str(&save).unwrap();
+        let original = World::with_generator(3, WorldParams::default(), GeneratorVersion::V1);
+        assert_eq!(world.seed(), 3);
+        assert_eq!(world.generator, GeneratorVersion::V1);
+        assert_eq!(world.params(), &WorldParams::default());
+        // the rotations follow the storage order of the map
+        let expected: Vec<TileConfig> = original.elements
+            .values()
+            .zip(rotations)
+            .map(|(tile, r)| tile.with_rotation(r))
+            .collect();
+        assert_eq!(world.elements.values().copied().collect::<Vec<_>>(), expected);
+        let resaved = serde_json::to_value(&world).unwrap();
+        assert_eq!(resaved["version"], SAVE_VERSION);
+        assert!(resaved.get("rotations").is_none());
+        assert_eq!(resaved["tiles"].as_array().unwrap().len(), 19);
+    }
 
-impl From<WorldSave> for World {
-    fn from(save: WorldSave) -> Self {
-        let mut world = World::new(save.seed);
-        if save.rotations.len() == world.elements.len() {
-            for (tc, r) in world.elements.values_mut().zip(save.rotations.iter()) {
-                *tc = tc.with_rotation(*r);
+    #[test]
+    fn painted_colors() {
+        let mut mixed = 0;
+        for seed in 0..10 {
+            let world = World::with_params(seed, hexagonal().with_colors(3));
+            assert!(world.is_completed(), "seed {}", seed);
+            let colors: Vec<HashSet<u8>> = world.elements
+                .values()
+                .map(|tile| tile.edges().into_iter().flatten().collect())
+                .collect();
+            assert!(colors.iter().flatten().all(|color| *color < 3), "seed {}", seed);
+            mixed += colors.iter().filter(|colors| colors.len() > 1).count();
+        }
+        // tiles can join differently colored parts of a network
+        assert!(mixed > 0);
+        let world = World::with_params(0, hexagonal());
+        assert!(world.elements.values().all(|tile| tile.colors() == [0; 6]));
+    }
+
+    #[test]
+    fn save_round_trip() {
+        let mut world = World::with_params(6, hexagonal().with_colors(2));
+        world.scramble(ScrambleParams::default().with_force_rotation(true)).unwrap();
+        let loaded: World = serde_json::from_str(&serde_json::to_string(&world).unwrap()).unwrap();
+        assert_eq!(loaded.seed(), world.seed());
+        assert_eq!(loaded.params(), world.params());
+        assert_eq!(loaded.generator, world.generator);
+        assert_eq!(loaded.locked, world.locked);
+        assert_eq!(loaded.incomplete, world.incomplete);
+        assert_eq!(loaded.to_string(), world.to_string());
+    }
+
+    #[test]
+    fn par() {
+        let world = World::with_params(1, square());
+        assert_eq!(world.par(), Some(0));
+        let pos = world.elements
+            .keys()
+            .find(|pos| world.elements[*pos].distance(world.elements[*pos].rotate_by(1)) == Some(1))
+            .unwrap();
+        let mut rotated = world.clone();
+        assert!(rotated.try_rotate(pos, Direction::CW));
+        assert_eq!(rotated.par(), Some(1));
+    }
+
+    // boards with several solutions can be finished with whichever solution is closest
+    #[test]
+    fn par_over_all_solutions() {
+        let params = WorldParams::default()
+            .with_topology(Topology::Square)
+            .with_radius(3);
+        let mut closer = 0;
+        for seed in 0..20 {
+            let mut world = World::with_params(seed, params.clone());
+            world.scramble(ScrambleParams::default().with_force_rotation(true)).unwrap();
+            let distances: Vec<u32> = world.solutions(usize::MAX)
+                .iter()
+                .map(|solution| world.elements
+                    .keys()
+                    .map(|pos| world.elements[pos].distance(solution.elements[pos]).unwrap() as u32)
+                    .sum())
+                .collect();
+            assert_eq!(world.par(), distances.iter().min().copied(), "seed {}", seed);
+            if distances[0] > world.par().unwrap() {
+                closer += 1;
             }
-            world.incomplete.clear();
-            for pos in world.elements.keys() {
-                if !world.is_tile_complete(pos) {
-                    world.incomplete.insert(pos);
-                }
+        }
+        assert!(closer > 0);
+    }
+
+    #[test]
+    fn hint() {
+        for seed in 0..5 {
+            let mut world = World::with_params(seed, hexagonal().with_mode(GenerationMode::UniqueSolution));
+            world.scramble(ScrambleParams::default().with_force_rotation(true)).unwrap();
+            let solutions = world.solutions(usize::MAX);
+            let mut hints = 0;
+            while let Some((pos, tile)) = world.hint() {
+                assert!(!world.is_locked(pos), "seed {}", seed);
+                assert_ne!(world.elements[pos].edges(), tile.edges(), "seed {}", seed);
+                assert!(solutions.iter().any(|s| s.elements[pos].edges() == tile.edges()), "seed {}", seed);
+                world.elements[pos] = tile;
+                hints += 1;
+                assert!(hints <= world.elements.len(), "seed {}", seed);
             }
-        } else {
-            log::warn!("Number of rotations in save doesn't match the number of tiles in this level");
+            world.update_incomplete();
+            assert!(world.is_completed(), "seed {}", seed);
         }
-        world
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn locked_tiles_dont_rotate() {
+        let mut world = World::with_params(2, hexagonal());
+        assert!(!world.locked.is_empty());
+        let before = world.to_string();
+        for pos in world.locked.clone() {
+            assert!(!world.try_rotate(pos, Direction::CW));
+            assert!(!world.try_rotate(pos, Direction::CCW));
+        }
+        assert_eq!(world.to_string(), before);
+        assert!(world.is_completed());
+        let pos = world.elements
+            .keys()
+            .find(|pos| !world.is_locked(*pos) && world.elements[*pos] != TileConfig::Empty)
+            .unwrap();
+        assert!(world.try_rotate(pos, Direction::CW));
+    }
+
+    #[test]
+    fn wrapped_connections() {
+        let params = WorldParams::default()
+            .with_topology(Topology::Square)
+            .with_radius(2)
+            .with_wrap(true);
+        let (mut world, pos) = (0..)
+            .map(|seed| World::with_params(seed, params.clone()))
+            .find_map(|world| world.elements
+                .keys()
+                .find(|pos| pos.q() == 2 && world.elements[*pos].edge(0).is_some())
+                .map(|pos| (world, pos)))
+            .unwrap();
+        let across = HexPos::new(-2, pos.r());
+        assert!(world.is_tile_complete(pos));
+        assert!(world.elements[across].edge(Topology::Square.opposite(0)).is_some());
+
+        // the connection across the edge breaks when the tile on the other side turns away
+        let original = world.elements[across];
+        world.elements[across] = (1..4)
+            .map(|r| original.rotate_by(r))
+            .find(|tile| tile.edge(Topology::Square.opposite(0)).is_none())
+            .unwrap();
+        assert!(!world.is_tile_complete(pos));
+
+        // without wrapping the same tile points out of the board
+        world.elements[across] = original;
+        world.elements = world.elements.clone().with_wrap(false);
+        assert!(!world.is_tile_complete(pos));
+    }
+
+    // broken saves have to fail to load instead of crashing the game
+    #[test]
+    fn invalid_saves() {
+        let mut weights = TileWeights::default().with_empty(0);
+        for tile_type in enum_iterator::all::<TileType>() {
+            weights = weights.with_tile(tile_type, 0);
+        }
+        let saves = [
+            String::from(r#"{"version": 2, "seed": 1, "params": {"radius": 1000}, "tiles": ["Empty"]}"#),
+            String::from(r#"{"version": 2, "seed": 1, "params": {"radius": -1}, "tiles": ["Empty"]}"#),
+            format!(r#"{{"seed": 1, "generator": "V2", "params": {}, "rotations": [0]}}"#, serde_json::to_string(&WorldParams::default().with_weights(weights)).unwrap()),
+            String::from(r#"{"version": 2, "seed": 1, "params": {"radius": 0}, "tiles": [{"Tile": ["Tile012345", 0, [0, 0, 4, 0, 0, 0]]}]}"#),
+            String::from(r#"{"version": 2, "seed": 1, "params": {"colors": 200}, "tiles": []}"#)
+        ];
+        for save in saves {
+            assert!(serde_json::from_str::<World>(&save).is_err(), "{}", save);
+        }
+        let valid = r#"{"version": 2, "seed": 1, "params": {"radius": 0}, "tiles": [{"Tile": ["Tile012345", 0, [0, 1, 2, 3, 2, 1]]}]}"#;
+        assert!(serde_json::from_str::<World>(valid).is_ok());
+    }
+
+}
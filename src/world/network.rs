@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+use hashbrown::HashSet;
+use crate::HexPos;
+use crate::world::tiles::TileConfig;
+use crate::world::World;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Network {
+    pub tiles: Vec<HexPos>,
+    // none of the tiles has an open end that isn't connected
+    pub closed: bool
+}
+
+impl World {
+
+    // splits the board into groups of tiles that are connected through matching endings
+    pub fn networks(&self) -> Vec<Network> {
+        let topology = self.elements.topology();
+        let mut visited = HashSet::new();
+        let mut networks = Vec::new();
+        for start in self.elements.keys() {
+            if self.elements[start] == TileConfig::Empty || !visited.insert(start) {
+                continue;
+            }
+            let mut network = Network {
+                tiles: Vec::new(),
+                closed: true
+            };
+            let mut queue = VecDeque::from([start]);
+            while let Some(pos) = queue.pop_front() {
+                network.tiles.push(pos);
+                let tile = self.elements[pos];
+                for (side, npos) in self.elements.neighbors(pos).enumerate() {
+                    if tile.edge(side).is_none() {
+                        continue;
+                    }
+                    let connected = self.elements
+                        .get(npos)
+                        .is_some_and(|n| n.edge(topology.opposite(side)) == tile.edge(side));
+                    network.closed &= connected;
+                    if connected && visited.insert(npos) {
+                        queue.push_back(npos);
+                    }
+                }
+            }
+            networks.push(network);
+        }
+        networks
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a closed loop on the left and a network on the right whose open end points into the loop
+    const BOARD: &str = "
+        topology: square
+        radius: 1
+        shape: full
+        wrap: false
+
+        03  06  02
+        09  0c  0c
+        00  00  00
+    ";
+
+    #[test]
+    fn adjacent_networks() {
+        let world: World = BOARD.parse().unwrap();
+        let mut networks = world.networks();
+        networks.sort_by_key(|network| network.tiles.len());
+        let [open, closed] = networks.as_slice() else {
+            panic!("expected two networks but found {}", networks.len());
+        };
+        assert!(!open.closed);
+        assert_eq!(open.tiles.iter().collect::<HashSet<_>>(), HashSet::from([&HexPos::new(1, 1), &HexPos::new(1, 0)]));
+        assert!(closed.closed);
+        assert_eq!(closed.tiles.len(), 4);
+    }
+
+}
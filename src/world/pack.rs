@@ -0,0 +1,161 @@
+use std::path::Path;
+use anyhow::Context;
+use serde::{Serialize, Deserialize};
+use crate::types::Topology;
+use crate::world::params::{BoardShape, GenerationMode, TileSet, WorldParams};
+use crate::world::{GeneratorVersion, World};
+
+const BUNDLED_PACKS: &[&[u8]] = &[
+    include_bytes!("../packs/tutorial.json")
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelPack {
+    pub name: String,
+    pub levels: Vec<PackLevel>
+}
+
+// a level is either generated from its seed or given explicitly as a layout in the plain-text board format
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PackLevel {
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    // keeps the layouts of shipped packs when the latest generator changes, packs without it were made with V2
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<GeneratorVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology: Option<Topology>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub radius: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shape: Option<BoardShape>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_set: Option<TileSet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<GenerationMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked_tiles: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colors: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<bool>
+}
+
+impl LevelPack {
+
+    pub fn from_json(data: &[u8]) -> anyhow::Result<Self> {
+        let pack: Self = serde_json::from_slice(data)?;
+        for (i, level) in pack.levels.iter().enumerate() {
+            if let Some(layout) = &level.layout {
+                layout
+                    .parse::<World>()
+                    .with_context(|| format!("invalid layout for level {} of \"{}\"", i + 1, pack.name))?;
+            }
+        }
+        Ok(pack)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let data = std::fs::read(path.as_ref())
+            .with_context(|| format!("can't read level pack {}", path.as_ref().display()))?;
+        Self::from_json(&data)
+    }
+
+    pub fn bundled() -> impl Iterator<Item=Self> {
+        BUNDLED_PACKS
+            .iter()
+            .map(|data| Self::from_json(data).expect("bundled level packs must be valid"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    pub fn level(&self, index: usize) -> Option<World> {
+        match self.levels.get(index)?.create() {
+            Ok(world) => Some(world),
+            Err(err) => {
+                log::warn!("Can't create level {} of \"{}\": {:#}", index + 1, self.name, err);
+                None
+            }
+        }
+    }
+
+}
+
+impl PackLevel {
+
+    pub fn params(&self) -> WorldParams {
+        let mut params = WorldParams::default()
+            .with_mode(self.mode.unwrap_or(GenerationMode::UniqueSolution));
+        if let Some(topology) = self.topology {
+            params = params.with_topology(topology);
+        }
+        if let Some(radius) = self.radius {
+            params = params.with_radius(radius);
+        }
+        if let Some(shape) = self.shape {
+            params = params.with_shape(shape);
+        }
+        if let Some(tile_set) = self.tile_set {
+            params = params.with_tile_set(tile_set);
+        }
+        if let Some(locked_tiles) = self.locked_tiles {
+            params = params.with_locked_tiles(locked_tiles);
+        }
+        if let Some(colors) = self.colors {
+            params = params.with_colors(colors);
+        }
+        if let Some(wrap) = self.wrap {
+            params = params.with_wrap(wrap);
+        }
+        params
+    }
+
+    pub fn create(&self) -> anyhow::Result<World> {
+        if let Some(layout) = &self.layout {
+            match layout.parse::<World>() {
+                Ok(mut world) => {
+                    // the layout only describes the board, the mode still tells how it has to be solvable
+                    if let Some(mode) = self.mode {
+                        world.params.mode = mode;
+                    }
+                    return Ok(world);
+                },
+                Err(err) => log::warn!("Falling back to a generated level because the layout is invalid: {:#}", err)
+            }
+        }
+        World::try_with_generator(self.seed, self.params(), self.generator())
+    }
+
+    pub fn generator(&self) -> GeneratorVersion {
+        self.version.unwrap_or(GeneratorVersion::V2)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generator_version() {
+        let pack = LevelPack::from_json(br#"{"name": "Versions", "levels": [
+            {"seed": 4, "radius": 3},
+            {"seed": 4, "radius": 3, "version": "V1"}
+        ]}"#).unwrap();
+        let params = pack.levels[0].params();
+        assert_eq!(pack.levels[0].generator(), GeneratorVersion::V2);
+        assert_eq!(pack.level(0).unwrap().to_string(), World::with_generator(4, params.clone(), GeneratorVersion::V2).to_string());
+        assert_eq!(pack.levels[1].generator(), GeneratorVersion::V1);
+        assert_eq!(pack.level(1).unwrap().to_string(), World::with_generator(4, params, GeneratorVersion::V1).to_string());
+        assert!(!serde_json::to_string(&pack.levels[0]).unwrap().contains("version"));
+    }
+
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use anyhow::{bail, ensure};
+use serde::{Serialize, Deserialize};
+use crate::HexPos;
+use crate::types::Topology;
+use crate::world::tiles::{TileConfig, TileType};
+
+// the renderer has a palette entry for every color
+pub const MAX_COLORS: u8 = 4;
+// keeps the size of the map and the solver's search reasonable
+pub const MAX_RADIUS: i32 = 64;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GenerationMode {
+    #[default]
+    Standard,
+    UniqueSolution
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TileSet {
+    #[default]
+    Standard,
+    Complete
+}
+
+impl FromStr for TileSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(TileSet::Standard),
+            "complete" => Ok(TileSet::Complete),
+            _ => bail!("unknown tile set \"{}\"", s)
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BoardShape {
+    #[default]
+    Full,
+    Ring(i32),
+    Rectangle,
+    Triangle,
+    Star
+}
+
+impl BoardShape {
+
+    pub fn contains(self, topology: Topology, radius: i32, pos: HexPos) -> bool {
+        let (q, r, s) = (pos.q(), pos.r(), pos.s());
+        match (self, topology) {
+            (BoardShape::Full, _) => true,
+            (BoardShape::Ring(hole), Topology::Hexagonal) => q.abs().max(r.abs()).max(s.abs()) >= hole,
+            (BoardShape::Ring(hole), Topology::Square) => q.abs().max(r.abs()) >= hole,
+            (BoardShape::Rectangle, Topology::Hexagonal) => {
+                let width = 2 * radius - radius / 2;
+                2 * r.abs() <= radius && -width <= 2 * q + r && 2 * q + r < width
+            },
+            (BoardShape::Rectangle, Topology::Square) => 2 * r.abs() <= radius,
+            (BoardShape::Triangle, Topology::Hexagonal) => 2 * q.min(r).min(s) >= -radius,
+            (BoardShape::Triangle, Topology::Square) => 2 * q.abs() <= radius - r,
+            (BoardShape::Star, Topology::Hexagonal) => 2 * q.min(r).min(s) >= -radius || 2 * q.max(r).max(s) <= radius,
+            (BoardShape::Star, Topology::Square) => 3 * q.abs() <= radius || 3 * r.abs() <= radius
+        }
+    }
+
+}
+
+impl FromStr for BoardShape {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["full"] => Ok(BoardShape::Full),
+            ["ring", hole] => Ok(BoardShape::Ring(hole.parse()?)),
+            ["rectangle"] => Ok(BoardShape::Rectangle),
+            ["triangle"] => Ok(BoardShape::Triangle),
+            ["star"] => Ok(BoardShape::Star),
+            _ => bail!("unknown shape \"{}\"", s)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TileWeights {
+    pub empty: u32,
+    pub tiles: BTreeMap<TileType, u32>
+}
+
+impl Default for TileWeights {
+    fn default() -> Self {
+        Self {
+            empty: 1,
+            tiles: BTreeMap::new()
+        }
+    }
+}
+
+impl TileWeights {
+
+    pub fn busy() -> Self {
+        Self::default()
+            .with_empty(0)
+            .with_tile(TileType::Tile0, 0)
+            .with_tile(TileType::Square0, 0)
+    }
+
+    pub fn relaxed() -> Self {
+        Self::default()
+            .with_empty(20)
+            .with_tile(TileType::Tile024, 0)
+            .with_tile(TileType::Tile0134, 0)
+            .with_tile(TileType::Square0123, 0)
+    }
+
+    pub fn with_empty(mut self, weight: u32) -> Self {
+        self.empty = weight;
+        self
+    }
+
+    pub fn with_tile(mut self, tile_type: TileType, weight: u32) -> Self {
+        self.tiles.insert(tile_type, weight);
+        self
+    }
+
+    // applies to every rotation of a tile type individually
+    pub fn get(&self, tile: TileConfig) -> u32 {
+        match tile {
+            TileConfig::Empty => self.empty,
+            TileConfig::Tile(tile_type, ..) => self.tiles.get(&tile_type).copied().unwrap_or(1)
+        }
+    }
+
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ScrambleParams {
+    // every rotatable tile ends up in a different orientation
+    pub force_rotation: bool,
+    // capped at the number of tiles that can be rotated
+    pub min_misplaced: usize,
+    // the same minimum as a percentage of the tiles that can be rotated
+    pub min_misplaced_percent: u32,
+    pub min_par: u32
+}
+
+impl ScrambleParams {
+
+    pub fn with_force_rotation(mut self, force_rotation: bool) -> Self {
+        self.force_rotation = force_rotation;
+        self
+    }
+
+    pub fn with_min_misplaced(mut self, min_misplaced: usize) -> Self {
+        self.min_misplaced = min_misplaced;
+        self
+    }
+
+    pub fn with_min_misplaced_percent(mut self, percent: u32) -> Self {
+        self.min_misplaced_percent = percent.min(100);
+        self
+    }
+
+    pub fn with_min_par(mut self, min_par: u32) -> Self {
+        self.min_par = min_par;
+        self
+    }
+
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldParams {
+    pub topology: Topology,
+    pub tile_set: TileSet,
+    pub shape: BoardShape,
+    pub radius: i32,
+    pub mode: GenerationMode,
+    pub locked_tiles: usize,
+    pub weights: TileWeights,
+    pub colors: u8,
+    pub wrap: bool
+}
+
+impl Default for WorldParams {
+    fn default() -> Self {
+        Self {
+            topology: Topology::Hexagonal,
+            tile_set: TileSet::Standard,
+            shape: BoardShape::Full,
+            radius: 2,
+            mode: GenerationMode::Standard,
+            locked_tiles: 0,
+            weights: TileWeights::default(),
+            colors: 1,
+            wrap: false
+        }
+    }
+}
+
+impl WorldParams {
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_tile_set(mut self, tile_set: TileSet) -> Self {
+        self.tile_set = tile_set;
+        self
+    }
+
+    pub fn with_shape(mut self, shape: BoardShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: i32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: GenerationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_locked_tiles(mut self, locked_tiles: usize) -> Self {
+        self.locked_tiles = locked_tiles;
+        self
+    }
+
+    pub fn with_weights(mut self, weights: TileWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_colors(mut self, colors: u8) -> Self {
+        self.colors = colors.clamp(1, MAX_COLORS);
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    // the builders keep the values in range, deserialized params have to be checked
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!((0..=MAX_RADIUS).contains(&self.radius), "radius {} is out of range", self.radius);
+        ensure!((1..=MAX_COLORS).contains(&self.colors), "{} colors are out of range", self.colors);
+        Ok(())
+    }
+
+}
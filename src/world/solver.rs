@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+use hashbrown::HashSet;
+use crate::HexPos;
+use crate::world::map::HexMap;
+use crate::world::params::MAX_COLORS;
+use crate::world::tiles::TileConfig;
+
+type RotationSet = u8;
+// the values an edge can take, the lowest bit stands for a closed side and the others for the colors
+type EdgeSet = u8;
+
+const _: () = assert!(MAX_COLORS < EdgeSet::BITS as u8);
+
+#[derive(Debug, Copy, Clone)]
+struct Cell {
+    tile: TileConfig,
+    edges: [[EdgeSet; 6]; 6],
+    // the rotations that are needed to get from the current rotation to each option
+    costs: [u8; 6],
+    options: RotationSet
+}
+
+impl Cell {
+
+    fn new(tile: TileConfig) -> Self {
+        let sides = match tile {
+            TileConfig::Empty => 1,
+            TileConfig::Tile(t, ..) => t.sides()
+        };
+        let mut edges = [[0; 6]; 6];
+        let mut costs = [0; 6];
+        let mut options = 0;
+        for rotation in 0..sides {
+            edges[rotation] = edge_sets(tile.with_rotation(rotation as u8));
+            costs[rotation] = tile.distance(tile.with_rotation(rotation as u8)).unwrap_or(0);
+            if !edges[..rotation].contains(&edges[rotation]) {
+                options |= 1 << rotation;
+            }
+        }
+        Self {
+            tile,
+            edges,
+            costs,
+            options
+        }
+    }
+
+    fn rotations(self) -> impl Iterator<Item=u8> {
+        (0..6).filter(move |r| self.options & (1 << r) != 0)
+    }
+
+    fn len(&self) -> u32 {
+        self.options.count_ones()
+    }
+
+    fn possible_edges(&self, side: usize) -> EdgeSet {
+        self.rotations().fold(0, |acc, r| acc | self.edges[r as usize][side])
+    }
+
+    fn min_cost(&self) -> u32 {
+        self.rotations().map(|r| self.costs[r as usize] as u32).min().unwrap_or(0)
+    }
+
+    fn solved(&self) -> TileConfig {
+        debug_assert_eq!(self.len(), 1);
+        self.tile.with_rotation(self.options.trailing_zeros() as u8)
+    }
+
+}
+
+fn edge_sets(tile: TileConfig) -> [EdgeSet; 6] {
+    tile.edges().map(|edge| match edge {
+        None => 1,
+        Some(color) => 2 << color
+    })
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct SolverStats {
+    pub deductions: usize,
+    pub rounds: usize,
+    pub guesses: usize,
+    pub max_depth: usize
+}
+
+struct Search {
+    limit: usize,
+    solutions: Vec<HexMap<TileConfig>>,
+    stats: SolverStats
+}
+
+impl Search {
+
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            solutions: Vec::new(),
+            stats: SolverStats::default()
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.solutions.len() >= self.limit
+    }
+
+}
+
+#[derive(Clone)]
+pub struct Solver {
+    cells: HexMap<Cell>,
+    propagation_queue: VecDeque<HexPos>
+}
+
+impl Solver {
+
+    pub fn new(tiles: &HexMap<TileConfig>) -> Self {
+        Self {
+            cells: HexMap::from(tiles, |tile| Cell::new(*tile)),
+            propagation_queue: tiles.keys().collect()
+        }
+    }
+
+    pub fn lock(mut self, positions: &HashSet<HexPos>) -> Self {
+        for pos in positions {
+            let cell = &mut self.cells[*pos];
+            let edges = edge_sets(cell.tile);
+            if let Some(rotation) = cell.edges.iter().position(|e| *e == edges) {
+                cell.options = 1 << rotation;
+            }
+        }
+        self
+    }
+
+    pub fn solve(mut self) -> Option<HexMap<TileConfig>> {
+        let mut search = Search::new(1);
+        self.search(0, &mut search);
+        search.solutions.pop()
+    }
+
+    pub fn solutions(mut self, limit: usize) -> Vec<HexMap<TileConfig>> {
+        let mut search = Search::new(limit);
+        self.search(0, &mut search);
+        search.solutions
+    }
+
+    pub fn analyze(mut self) -> Option<SolverStats> {
+        let mut search = Search::new(1);
+        self.search(0, &mut search);
+        search.is_done().then_some(search.stats)
+    }
+
+    // the fewest rotations that turn the current tiles into any of the solutions
+    pub fn min_rotations(mut self) -> Option<u32> {
+        let mut best = None;
+        self.search_cheapest(&mut best);
+        best
+    }
+
+    fn search(&mut self, depth: usize, search: &mut Search) {
+        if self.propagate(&mut search.stats).is_err() {
+            return;
+        }
+        search.stats.max_depth = search.stats.max_depth.max(depth);
+        match self.most_constrained() {
+            None => search.solutions.push(HexMap::from(&self.cells, Cell::solved)),
+            Some(pos) => for rotation in self.cells[pos].rotations() {
+                if search.is_done() {
+                    break;
+                }
+                search.stats.guesses += 1;
+                let mut branch = self.clone();
+                branch.cells[pos].options = 1 << rotation;
+                branch.propagation_queue.extend(self.cells.neighbors(pos));
+                branch.search(depth + 1, search);
+            }
+        }
+    }
+
+    fn search_cheapest(&mut self, best: &mut Option<u32>) {
+        if self.propagate(&mut SolverStats::default()).is_err() {
+            return;
+        }
+        // every tile needs at least the rotations to its cheapest remaining option
+        let bound: u32 = self.cells.values().map(Cell::min_cost).sum();
+        if best.is_some_and(|best| bound >= best) {
+            return;
+        }
+        match self.most_constrained() {
+            None => *best = Some(bound),
+            Some(pos) => {
+                let cell = self.cells[pos];
+                let mut rotations: Vec<u8> = cell.rotations().collect();
+                rotations.sort_by_key(|r| cell.costs[*r as usize]);
+                for rotation in rotations {
+                    let mut branch = self.clone();
+                    branch.cells[pos].options = 1 << rotation;
+                    branch.propagation_queue.extend(self.cells.neighbors(pos));
+                    branch.search_cheapest(best);
+                }
+            }
+        }
+    }
+
+    fn most_constrained(&self) -> Option<HexPos> {
+        self.cells
+            .keys()
+            .filter(|pos| self.cells[*pos].len() > 1)
+            .min_by_key(|pos| self.cells[*pos].len())
+    }
+
+    fn propagate(&mut self, stats: &mut SolverStats) -> Result<(), ()> {
+        let mut round_end = self.propagation_queue.len();
+        let mut round_deductions = 0;
+        while let Some(pos) = self.propagation_queue.pop_front() {
+            if round_end == 0 {
+                round_end = self.propagation_queue.len() + 1;
+                if round_deductions > 0 {
+                    stats.rounds += 1;
+                    round_deductions = 0;
+                }
+            }
+            round_end -= 1;
+            let cell = match self.cells.get(pos) {
+                None => continue,
+                Some(cell) => *cell
+            };
+            let topology = self.cells.topology();
+            let mut options = cell.options;
+            for (side, neighbor) in self.cells.neighbors(pos).enumerate() {
+                let possible = match self.cells.get(neighbor) {
+                    Some(n) => n.possible_edges(topology.opposite(side)),
+                    None => 1
+                };
+                for rotation in cell.rotations() {
+                    if cell.edges[rotation as usize][side] & possible == 0 {
+                        options &= !(1 << rotation);
+                    }
+                }
+            }
+            if options != cell.options {
+                if options == 0 {
+                    return Err(());
+                }
+                if options.count_ones() == 1 {
+                    stats.deductions += 1;
+                    round_deductions += 1;
+                }
+                self.cells[pos].options = options;
+                self.propagation_queue.extend(self.cells.neighbors(pos));
+            }
+        }
+        if round_deductions > 0 {
+            stats.rounds += 1;
+        }
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Topology;
+    use crate::world::params::{BoardShape, ScrambleParams, WorldParams};
+    use crate::world::tiles::TileType;
+    use crate::world::World;
+
+    fn scrambled(seed: u64, params: WorldParams) -> World {
+        let mut world = World::with_params(seed, params);
+        world.scramble(ScrambleParams::default().with_force_rotation(true)).unwrap();
+        world
+    }
+
+    #[test]
+    fn solved_tiles_are_complete() {
+        let boards = [
+            WorldParams::default().with_radius(4),
+            WorldParams::default().with_topology(Topology::Square).with_radius(3),
+            WorldParams::default().with_radius(3).with_colors(3).with_wrap(true)
+        ];
+        for params in boards {
+            for seed in 0..5 {
+                let world = scrambled(seed, params.clone());
+                let solution = Solver::new(&world.elements).solve().unwrap();
+                let solved = World::from_tiles(seed, params.clone(), solution, HashSet::new());
+                assert!(solved.elements.keys().all(|pos| solved.is_tile_complete(pos)), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn colors_restrict_solutions() {
+        // wrapped boards tend to have several solutions without colors
+        let params = WorldParams::default()
+            .with_topology(Topology::Square)
+            .with_radius(3)
+            .with_wrap(true)
+            .with_colors(3);
+        let mut restricted = 0;
+        for seed in 0..10 {
+            let world = scrambled(seed, params.clone());
+            let uncolored = HexMap::from(&world.elements, |tile| tile.with_colors([0; 6]));
+            let colored = Solver::new(&world.elements).solutions(64).len();
+            let plain = Solver::new(&uncolored).solutions(64).len();
+            assert!((1..=plain).contains(&colored), "seed {}", seed);
+            if colored < plain {
+                restricted += 1;
+            }
+        }
+        assert!(restricted > 0);
+    }
+
+    #[test]
+    fn unsolvable() {
+        // a single dead end always points out of the board
+        let mut tiles = HexMap::new(Topology::Hexagonal, BoardShape::Full, 0);
+        tiles[HexPos::CENTER] = TileConfig::Tile(TileType::Tile0, 0, [0; 6]);
+        assert!(Solver::new(&tiles).solve().is_none());
+        assert!(Solver::new(&tiles).solutions(2).is_empty());
+        assert!(Solver::new(&tiles).analyze().is_none());
+    }
+
+    #[test]
+    fn locked_tiles_keep_rotation() {
+        for seed in 0..5 {
+            let world = scrambled(seed, WorldParams::default().with_radius(3).with_locked_tiles(4));
+            assert!(!world.locked.is_empty());
+            for solution in Solver::new(&world.elements).lock(&world.locked).solutions(8) {
+                for pos in &world.locked {
+                    assert_eq!(solution[*pos].edges(), world.elements[*pos].edges(), "seed {}", seed);
+                }
+            }
+            // a locked tile in the wrong rotation stays wrong, even if that leaves no solution
+            let mut tiles = world.elements.clone();
+            let pos = *world.locked.iter().next().unwrap();
+            tiles[pos] = tiles[pos].rotate_by(1);
+            for solution in Solver::new(&tiles).lock(&world.locked).solutions(8) {
+                assert_eq!(solution[pos].edges(), tiles[pos].edges(), "seed {}", seed);
+            }
+        }
+    }
+
+}
@@ -0,0 +1,291 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use anyhow::{anyhow, bail, ensure, Context};
+use hashbrown::HashSet;
+use crate::HexPos;
+use crate::types::Topology;
+use crate::world::map::HexMap;
+use crate::world::params::{BoardShape, TileSet, WorldParams, MAX_COLORS, MAX_RADIUS};
+use crate::world::tiles::{TileConfig, TileType};
+use crate::world::World;
+
+// A board is written as a header of "key: value" lines, followed by an empty line and one line per row
+// (top to bottom). Every cell is the bitmask of its open sides as two hex digits, optionally followed by
+// ":<colors>" and "!" for locked tiles. The colors are a single digit for all endings or one digit per open
+// side in the order of the bitmask. Cells that are cut out of the board shape are written as "..".
+//
+//     topology: hexagonal
+//     radius: 1
+//     shape: full
+//     wrap: false
+//     seed: 3
+//
+//       02  00
+//     02  15  0c
+//       30  20
+
+const MASKED: &str = "..";
+
+fn topology_name(topology: Topology) -> &'static str {
+    match topology {
+        Topology::Hexagonal => "hexagonal",
+        Topology::Square => "square"
+    }
+}
+
+fn shape_name(shape: BoardShape) -> String {
+    match shape {
+        BoardShape::Full => String::from("full"),
+        BoardShape::Ring(hole) => format!("ring {}", hole),
+        BoardShape::Rectangle => String::from("rectangle"),
+        BoardShape::Triangle => String::from("triangle"),
+        BoardShape::Star => String::from("star")
+    }
+}
+
+fn parse_header(params: &mut WorldParams, seed: &mut u64, key: &str, value: &str) -> anyhow::Result<()> {
+    match key {
+        "topology" => params.topology = value.parse()?,
+        "radius" => {
+            params.radius = value.parse()?;
+            ensure!((0..=MAX_RADIUS).contains(&params.radius), "radius {} is out of range", params.radius);
+        },
+        "shape" => params.shape = value.parse()?,
+        "wrap" => params.wrap = value.parse()?,
+        "seed" => *seed = value.parse()?,
+        _ => bail!("unknown key \"{}\"", key)
+    }
+    Ok(())
+}
+
+fn mask(tile: TileConfig) -> u8 {
+    tile.endings()
+        .iter()
+        .enumerate()
+        .filter(|(_, open)| **open)
+        .fold(0, |acc, (i, _)| acc | (1 << i))
+}
+
+fn tile_from_mask(topology: Topology, mask: u8) -> Option<TileConfig> {
+    if mask == 0 {
+        return Some(TileConfig::Empty);
+    }
+    enum_iterator::all::<TileType>()
+        .filter(|t| t.topology() == topology)
+        .flat_map(|t| (0..t.sides() as u8).map(move |r| TileConfig::Tile(t, r, [0; 6])))
+        .find(|tile| self::mask(*tile) == mask)
+}
+
+fn cell(tile: TileConfig, locked: bool) -> String {
+    let mut cell = format!("{:02x}", mask(tile));
+    let colors: Vec<u8> = tile.edges().into_iter().flatten().collect();
+    if colors.iter().any(|color| *color != colors[0]) {
+        cell += ":";
+        cell.extend(colors.iter().map(|color| char::from(b'0' + color)));
+    } else if colors.first().is_some_and(|color| *color != 0) {
+        cell += &format!(":{}", colors[0]);
+    }
+    if locked {
+        cell += "!";
+    }
+    cell
+}
+
+fn parse_cell(topology: Topology, s: &str) -> anyhow::Result<(TileConfig, bool)> {
+    let (s, locked) = match s.strip_suffix('!') {
+        Some(s) => (s, true),
+        None => (s, false)
+    };
+    let (mask, colors) = match s.split_once(':') {
+        Some((mask, colors)) => (mask, colors
+            .chars()
+            .map(|c| c.to_digit(10).map(|d| d as u8).ok_or_else(|| anyhow!("{} is not a color", c)))
+            .collect::<anyhow::Result<Vec<u8>>>()?),
+        None => (s, vec![0])
+    };
+    if let Some(color) = colors.iter().find(|color| **color >= MAX_COLORS) {
+        bail!("color {} is out of range", color);
+    }
+    let mask = u8::from_str_radix(mask, 16)?;
+    let tile = tile_from_mask(topology, mask)
+        .ok_or_else(|| anyhow!("{:02x} is not a valid tile", mask))?;
+    let sides: Vec<usize> = (0..6).filter(|side| tile.endings()[*side]).collect();
+    let mut edge_colors = [0; 6];
+    match colors.as_slice() {
+        [color] => edge_colors = [*color; 6],
+        colors if colors.len() == sides.len() => for (side, color) in sides.iter().zip(colors) {
+            edge_colors[*side] = *color;
+        },
+        colors => bail!("{} colors for {} endings", colors.len(), sides.len())
+    }
+    Ok((tile.with_colors(edge_colors), locked))
+}
+
+fn rows(topology: Topology, radius: i32) -> impl Iterator<Item=Vec<HexPos>> {
+    (-radius..=radius).rev().map(move |r| (-radius..=radius)
+        .map(|q| HexPos::new(q, r))
+        .filter(|pos| topology.in_bounds(radius, *pos))
+        .collect())
+}
+
+impl Display for World {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (topology, radius) = (self.params.topology, self.params.radius);
+        writeln!(f, "topology: {}", topology_name(topology))?;
+        writeln!(f, "radius: {}", radius)?;
+        writeln!(f, "shape: {}", shape_name(self.params.shape))?;
+        writeln!(f, "wrap: {}", self.params.wrap)?;
+        writeln!(f, "seed: {}", self.seed)?;
+        writeln!(f)?;
+
+        let cells: Vec<Vec<(HexPos, String)>> = rows(topology, radius)
+            .map(|row| row
+                .into_iter()
+                .map(|pos| (pos, match self.elements.get(pos) {
+                    Some(tile) => cell(*tile, self.is_locked(pos)),
+                    None => String::from(MASKED)
+                }))
+                .collect())
+            .collect();
+        let width = cells.iter().flatten().map(|(_, s)| s.len()).max().unwrap_or(0) + 1;
+        let width = width + width % 2;
+        let column = |pos: HexPos| match topology {
+            Topology::Hexagonal => width as i32 * pos.q() + width as i32 / 2 * pos.r(),
+            Topology::Square => width as i32 * pos.q()
+        };
+        let start = cells.iter().flatten().map(|(pos, _)| column(*pos)).min().unwrap_or(0);
+        for row in cells {
+            let mut line = String::new();
+            for (pos, s) in row {
+                let indent = (column(pos) - start) as usize;
+                line += &" ".repeat(indent.saturating_sub(line.len()));
+                line += &s;
+            }
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for World {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap().trim()));
+
+        let mut params = WorldParams::default();
+        let mut seed = 0;
+        let header = lines
+            .by_ref()
+            .skip_while(|(_, line)| line.is_empty())
+            .take_while(|(_, line)| !line.is_empty());
+        for (i, line) in header {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("expected \"key: value\" in line {}", i))?;
+            parse_header(&mut params, &mut seed, key.trim(), value.trim())
+                .with_context(|| format!("invalid header in line {}", i))?;
+        }
+        ensure!(params.radius >= 0, "the radius must not be negative");
+
+        let topology = params.topology;
+        let mut tiles = HexMap::new(topology, params.shape, params.radius).with_wrap(params.wrap);
+        let mut locked = HashSet::new();
+        let mut lines = lines.filter(|(_, line)| !line.is_empty());
+        for row in rows(topology, params.radius) {
+            let (i, line) = lines.next().ok_or_else(|| anyhow!("the board has too few rows"))?;
+            let cells: Vec<&str> = line.split_whitespace().collect();
+            ensure!(cells.len() == row.len(), "expected {} cells in line {} but found {}", row.len(), i, cells.len());
+            for (pos, s) in row.into_iter().zip(cells) {
+                match (tiles.contains(pos), s) {
+                    (false, MASKED) => {},
+                    (false, _) => bail!("expected {} in line {} for a cell outside of the board shape", MASKED, i),
+                    (true, _) => {
+                        let (tile, is_locked) = parse_cell(topology, s)
+                            .with_context(|| format!("invalid cell \"{}\" in line {}", s, i))?;
+                        tiles[pos] = tile;
+                        if is_locked {
+                            locked.insert(pos);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some((i, _)) = lines.next() {
+            bail!("unexpected line {} after the last row", i);
+        }
+
+        params.colors = tiles.values().flat_map(|t| t.edges()).flatten().map(|c| c + 1).max().unwrap_or(1);
+        params.locked_tiles = locked.len();
+        if tiles.values().any(|t| matches!(t, TileConfig::Tile(t, ..) if t.tile_set() == TileSet::Complete)) {
+            params.tile_set = TileSet::Complete;
+        }
+        Ok(World::from_tiles(seed, params, tiles, locked))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &[&str] = &[
+        include_str!("fixtures/text/colors.txt"),
+        include_str!("fixtures/text/ring.txt"),
+        include_str!("fixtures/text/square.txt"),
+        include_str!("fixtures/text/star.txt")
+    ];
+
+    fn assert_same(a: &World, b: &World) {
+        assert_eq!(a.seed, b.seed);
+        assert_eq!(a.params.topology, b.params.topology);
+        assert_eq!(a.params.radius, b.params.radius);
+        assert_eq!(a.params.shape, b.params.shape);
+        assert_eq!(a.params.wrap, b.params.wrap);
+        assert_eq!(a.locked, b.locked);
+        assert_eq!(a.elements.keys().collect::<Vec<_>>(), b.elements.keys().collect::<Vec<_>>());
+        for pos in a.elements.keys() {
+            assert_eq!(mask(a.elements[pos]), mask(b.elements[pos]), "tile at {:?}", pos);
+            assert_eq!(a.elements[pos].edges(), b.elements[pos].edges(), "colors at {:?}", pos);
+        }
+    }
+
+    #[test]
+    fn fixtures() {
+        for fixture in FIXTURES {
+            let world: World = fixture.parse().unwrap();
+            assert_eq!(&world.to_string(), fixture);
+            assert_same(&world, &world.to_string().parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn generated() {
+        let boards = [
+            WorldParams::default().with_radius(3).with_colors(MAX_COLORS).with_locked_tiles(4),
+            WorldParams::default().with_radius(4).with_shape(BoardShape::Ring(2)),
+            WorldParams::default().with_topology(Topology::Square).with_radius(3).with_wrap(true)
+        ];
+        for params in boards {
+            for seed in 0..10 {
+                let world = World::with_params(seed, params.clone());
+                assert_same(&world, &world.to_string().parse().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let board = FIXTURES[0];
+        assert!(board.replace("05:2!", "05:9!").parse::<World>().is_err());
+        assert!(board.replace("05:2!", "zz").parse::<World>().is_err());
+        // one color per open side or a single one for all of them
+        assert!(board.replace("05:2!", "05:123!").parse::<World>().is_err());
+        assert!(board.replace("05:2!", "05:x!").parse::<World>().is_err());
+        assert!(board.replace("radius: 2", "radius: 3").parse::<World>().is_err());
+        assert!(FIXTURES[1].replace("..", "00").parse::<World>().is_err());
+    }
+
+}
@@ -1,8 +1,11 @@
 use std::fmt::Debug;
 use enum_iterator::Sequence;
-use crate::types::Angle;
+use serde::{Serialize, Deserialize};
+use crate::types::{Angle, Topology};
+use crate::world::Direction;
+use crate::world::params::TileSet;
 
-#[derive(Debug, Sequence, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Sequence, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum TileType {
     Tile0,
     Tile01,
@@ -11,6 +14,17 @@ pub enum TileType {
     Tile012,
     Tile024,
     Tile0134,
+    Tile013,
+    Tile014,
+    Tile0123,
+    Tile0124,
+    Tile01234,
+    Tile012345,
+    Square0,
+    Square01,
+    Square02,
+    Square012,
+    Square0123,
 }
 
 impl TileType {
@@ -23,8 +37,47 @@ impl TileType {
             TileType::Tile012 => 5,
             TileType::Tile024 => 6,
             TileType::Tile0134 => 7,
+            TileType::Square0 => 8,
+            TileType::Square01 => 9,
+            TileType::Square02 => 10,
+            TileType::Square012 => 11,
+            TileType::Square0123 => 12,
+            TileType::Tile013 => 13,
+            TileType::Tile014 => 14,
+            TileType::Tile0123 => 15,
+            TileType::Tile0124 => 16,
+            TileType::Tile01234 => 17,
+            TileType::Tile012345 => 18,
         }
     }
+
+    pub fn tile_set(self) -> TileSet {
+        match self {
+            TileType::Tile013 |
+            TileType::Tile014 |
+            TileType::Tile0123 |
+            TileType::Tile0124 |
+            TileType::Tile01234 |
+            TileType::Tile012345 => TileSet::Complete,
+            _ => TileSet::Standard
+        }
+    }
+
+    pub fn topology(self) -> Topology {
+        match self {
+            TileType::Square0 |
+            TileType::Square01 |
+            TileType::Square02 |
+            TileType::Square012 |
+            TileType::Square0123 => Topology::Square,
+            _ => Topology::Hexagonal
+        }
+    }
+
+    pub fn sides(self) -> usize {
+        self.topology().sides()
+    }
+
     pub fn endings(self) -> [bool; 6] {
         match self {
             TileType::Tile0 => [false, false, false, false, false, true],
@@ -34,14 +87,40 @@ impl TileType {
             TileType::Tile012 => [true, true, false, false, false, true],
             TileType::Tile024 => [false, true, false, true, false, true],
             TileType::Tile0134 => [true, false, true, true, false, true],
+            TileType::Tile013 => [true, false, true, false, false, true],
+            TileType::Tile014 => [true, false, false, true, false, true],
+            TileType::Tile0123 => [true, true, true, false, false, true],
+            TileType::Tile0124 => [true, true, false, true, false, true],
+            TileType::Tile01234 => [true, true, true, true, false, true],
+            TileType::Tile012345 => [true, true, true, true, true, true],
+            TileType::Square0 => [false, false, false, true, false, false],
+            TileType::Square01 => [true, false, false, true, false, false],
+            TileType::Square02 => [false, true, false, true, false, false],
+            TileType::Square012 => [true, true, false, true, false, false],
+            TileType::Square0123 => [true, true, true, true, false, false],
         }
     }
+
+    pub fn connections(self) -> usize {
+        self.endings().iter().filter(|e| **e).count()
+    }
+
+    pub fn period(self) -> u8 {
+        let sides = self.sides() as u8;
+        let tile = TileConfig::Tile(self, 0, [0; 6]);
+        (1..sides)
+            .find(|r| tile.rotate_by(*r).endings() == tile.endings())
+            .unwrap_or(sides)
+    }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+// the color of every ending, indexed by the sides of the unrotated tile type
+pub type EdgeColors = [u8; 6];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum TileConfig {
     Empty,
-    Tile(TileType, u8),
+    Tile(TileType, u8, EdgeColors),
 }
 
 impl Default for TileConfig {
@@ -55,9 +134,9 @@ impl TileConfig {
     pub fn endings(self) -> [bool; 6] {
         match self {
             TileConfig::Empty => [false; 6],
-            TileConfig::Tile(tile_type, rotation) => {
+            TileConfig::Tile(tile_type, rotation, _) => {
                 let mut endings = tile_type.endings();
-                endings.rotate_right(rotation as usize);
+                endings[..tile_type.sides()].rotate_right(rotation as usize);
                 endings
             }
         }
@@ -66,42 +145,140 @@ impl TileConfig {
     pub fn rotation(self) -> u8 {
         match self {
             TileConfig::Empty => 0,
-            TileConfig::Tile(_, r) => r,
+            TileConfig::Tile(_, r, _) => r,
         }
     }
 
+    // the colors in the orientation of the board, closed sides are 0
+    pub fn colors(self) -> [u8; 6] {
+        match self {
+            TileConfig::Empty => [0; 6],
+            TileConfig::Tile(tile_type, rotation, mut colors) => {
+                colors[..tile_type.sides()].rotate_right(rotation as usize);
+                colors
+            }
+        }
+    }
+
+    // takes the colors in the orientation of the board
+    pub fn with_colors(self, mut colors: [u8; 6]) -> Self {
+        match self {
+            TileConfig::Empty => TileConfig::Empty,
+            TileConfig::Tile(t, r, _) => {
+                colors[..t.sides()].rotate_left(r as usize);
+                for (color, open) in colors.iter_mut().zip(t.endings()) {
+                    if !open {
+                        *color = 0;
+                    }
+                }
+                TileConfig::Tile(t, r, colors)
+            }
+        }
+    }
+
+    pub fn edge(self, side: usize) -> Option<u8> {
+        self.endings()[side].then(|| self.colors()[side])
+    }
+
+    pub fn edges(self) -> [Option<u8>; 6] {
+        std::array::from_fn(|side| self.edge(side))
+    }
+
     //pub fn is_empty(self) -> bool {
     //    match self {
     //        TileConfig::Empty => true,
-    //        TileConfig::Tile(_, _) => false
+    //        TileConfig::Tile(..) => false
     //    }
     //}
 
     pub fn angle(self) -> Angle {
-        Angle::radians(-std::f32::consts::FRAC_PI_3 * self.rotation() as f32)
+        match self {
+            TileConfig::Empty => Angle::empty(),
+            TileConfig::Tile(t, r, _) => Angle::radians(-std::f32::consts::TAU / t.sides() as f32 * r as f32)
+        }
     }
 
     pub fn rotate_by(self, d: u8) -> Self {
         match self {
             TileConfig::Empty => TileConfig::Empty,
-            TileConfig::Tile(t, r) => TileConfig::Tile(t, (r + d) % 6),
+            TileConfig::Tile(t, r, c) => TileConfig::Tile(t, (r + d) % t.sides() as u8, c),
+        }
+    }
+
+    pub fn rotate(self, direction: Direction) -> Self {
+        match (self, direction) {
+            (TileConfig::Empty, _) => TileConfig::Empty,
+            (TileConfig::Tile(..), Direction::CW) => self.rotate_by(1),
+            (TileConfig::Tile(t, ..), Direction::CCW) => self.rotate_by(t.sides() as u8 - 1)
         }
     }
 
     pub fn with_rotation(self, r: u8) -> Self {
         match self {
             TileConfig::Empty => TileConfig::Empty,
-            TileConfig::Tile(t, _) => TileConfig::Tile(t, r)
+            TileConfig::Tile(t, _, c) => TileConfig::Tile(t, r % t.sides() as u8, c)
         }
     }
 
+    pub fn distance(self, target: Self) -> Option<u8> {
+        let sides = match self {
+            TileConfig::Empty => 1,
+            TileConfig::Tile(t, ..) => t.sides() as u8
+        };
+        (0..sides)
+            .filter(|i| self.rotate_by(*i).edges() == target.edges())
+            .map(|i| u8::min(i, sides - i))
+            .min()
+    }
+
     pub fn model(self) -> usize {
         match self {
             TileConfig::Empty => 0,
-            TileConfig::Tile(t, _) => t.model(),
+            TileConfig::Tile(t, ..) => t.model(),
         }
     }
 }
 
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        let tile = TileConfig::Tile(TileType::Tile01, 0, [0; 6]);
+        assert_eq!(tile.distance(tile), Some(0));
+        assert_eq!(tile.distance(tile.rotate_by(2)), Some(2));
+        assert_eq!(tile.distance(tile.rotate_by(3)), Some(3));
+        // going the other way around is shorter
+        assert_eq!(tile.distance(tile.rotate_by(5)), Some(1));
+        // symmetric tiles reach an equivalent rotation earlier
+        let symmetric = TileConfig::Tile(TileType::Tile024, 0, [0; 6]);
+        assert_eq!(symmetric.distance(symmetric.rotate_by(1)), Some(1));
+        assert_eq!(symmetric.distance(symmetric.rotate_by(2)), Some(0));
+        let square = TileConfig::Tile(TileType::Square01, 1, [0; 6]);
+        assert_eq!(square.distance(square.rotate_by(3)), Some(1));
+        assert_eq!(square.distance(square.rotate_by(2)), Some(2));
+        assert_eq!(TileConfig::Empty.distance(TileConfig::Empty), Some(0));
+        assert_eq!(tile.distance(TileConfig::Tile(TileType::Tile02, 0, [0; 6])), None);
+        assert_eq!(tile.distance(TileConfig::Empty), None);
+        // the colors of the endings have to line up as well
+        let colored = symmetric.with_colors([0, 1, 0, 2, 0, 3]);
+        assert_eq!(colored.distance(colored.rotate_by(2)), Some(2));
+        assert_eq!(colored.distance(colored.with_colors([0, 1, 0, 1, 0, 1])), None);
+    }
+
+    #[test]
+    fn colors() {
+        let tile = TileConfig::Tile(TileType::Tile01, 0, [0; 6]).with_colors([1, 2, 3, 3, 3, 2]);
+        assert_eq!(tile.edges(), [Some(1), None, None, None, None, Some(2)]);
+        let rotated = tile.rotate_by(1);
+        assert_eq!(rotated.edges(), [Some(2), Some(1), None, None, None, None]);
+        assert_eq!(rotated.with_colors(rotated.colors()), rotated);
+        let square = TileConfig::Tile(TileType::Square02, 3, [0; 6]).with_colors([0, 0, 1, 0, 2, 2]);
+        assert_eq!(square.edges(), [Some(0), None, Some(1), None, None, None]);
+    }
+
+}